@@ -1,22 +1,787 @@
-use clap::Parser;
-use miette::Result;
+use clap::{Args, Parser, Subcommand};
+use miette::{Context, IntoDiagnostic, Result};
+use std::fs;
 use std::path::PathBuf;
-use qcore::estimate;
+use std::process::ExitCode;
+use std::time::Duration;
+use qcore::git::{FileChange, FileEstimate};
+use qcore::{
+    default_estimation_config, estimate_config_diff, estimate_frontier_logical_counts, estimate_logical_counts, estimate_program,
+    estimate_logical_counts_sensitivity, estimate_program_frontier, estimate_program_sensitivity,
+    estimate_program_diff, estimate_program_sweep, EstimationConfig, EstimationDiff, EstimationOutput, ErrorBudget, FrontierOutput,
+    Budget, LogicalCounts, OutputFormat, Program, QecScheme, QubitParams, Registry, ResultCache, Sensitivity, SweepSpec,
+    TargetProfile, Thresholds,
+};
+
+/// Exit status when an estimate is over its budget, a budget annotation is malformed, or a diff finds regressions.
+const OVER_BUDGET: u8 = 3;
 
 #[derive(Parser)]
+#[command(
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    after_help = "Exit status: 0 on success, 1 when a program cannot be estimated, 2 for invalid arguments, \
+        3 when an estimate is over its budget or a diff finds regressions."
+)]
 struct Cli {
-    /// Path to the Q# file to estimate
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to the Q# file, or to a project directory with a qsharp.json manifest, to estimate
+    #[arg(required = true)]
+    path: Option<PathBuf>,
+
+    /// Expression to estimate instead of the @EntryPoint() operation, e.g. "Sample.QFT(64)"
+    #[arg(long)]
+    entry: Option<String>,
+
+    /// Target profile to compile the program for: base, adaptive_ri or unrestricted
+    #[arg(long, default_value_t = TargetProfile::default())]
+    profile: TargetProfile,
+
+    /// Directory to cache estimation results in, so unchanged programs are not estimated again
+    #[arg(long, conflicts_with = "frontier")]
+    cache: Option<PathBuf>,
+
+    /// Seconds a cached result stays valid; forever if not given
+    #[arg(long, requires = "cache")]
+    cache_ttl: Option<u64>,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+
+    #[command(flatten)]
+    budget: BudgetArgs,
+
+    #[command(flatten)]
+    mode: ModeArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Estimate an algorithm from its logical counts, without a Q# program
+    Counts {
+        #[command(flatten)]
+        counts: CountsArgs,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+
+        #[command(flatten)]
+        budget: BudgetArgs,
+
+        #[command(flatten)]
+        mode: ModeArgs,
+    },
+
+    /// Estimate a program under every combination of qubit models, QEC schemes, error budgets and constraints
+    Sweep(SweepArgs),
+
+    /// Compare the estimates of two versions of a program, or of one program on two hardware profiles
+    Diff(DiffArgs),
+
+    /// Compare the estimates of every Q# program (file or project) changed between two revisions of a git repository
+    DiffRevisions(RevisionsArgs),
+
+    /// List the built-in qubit models and QEC schemes
+    Presets {
+        /// Print every preset with its full parameters as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Args)]
+struct CountsArgs {
+    /// Number of logical qubits the algorithm uses
+    #[arg(long)]
+    num_qubits: u64,
+
+    /// Number of T gates
+    #[arg(long, default_value_t = 0)]
+    t_count: u64,
+
+    /// Number of arbitrary-angle rotations
+    #[arg(long, default_value_t = 0)]
+    rotation_count: u64,
+
+    /// Depth of the rotations
+    #[arg(long, default_value_t = 0)]
+    rotation_depth: u64,
+
+    /// Number of CCZ gates
+    #[arg(long, default_value_t = 0)]
+    ccz_count: u64,
+
+    /// Number of CCiX gates
+    #[arg(long, default_value_t = 0)]
+    ccix_count: u64,
+
+    /// Number of single-qubit measurements
+    #[arg(long, default_value_t = 0)]
+    measurement_count: u64,
+}
+
+#[derive(Args)]
+struct SweepArgs {
+    /// Path to the Q# file, or to a project directory with a qsharp.json manifest, to estimate
     path: PathBuf,
+
+    /// Expression to estimate instead of the @EntryPoint() operation, e.g. "Sample.QFT(64)"
+    #[arg(long)]
+    entry: Option<String>,
+
+    /// Target profile to compile the program for; overrides the spec's
+    #[arg(long)]
+    profile: Option<TargetProfile>,
+
+    /// Sweep spec file (TOML or JSON) with the qubitParams, qecSchemes, errorBudgets and constraints to combine
+    #[arg(long)]
+    spec: Option<PathBuf>,
+
+    /// Qubit model presets to sweep, added to the spec's, e.g. qubit_gate_ns_e3,qubit_maj_ns_e4
+    #[arg(long, value_delimiter = ',')]
+    qubit_params: Vec<String>,
+
+    /// QEC scheme presets to sweep, added to the spec's, e.g. surface_code,floquet_code
+    #[arg(long, value_delimiter = ',')]
+    qec_schemes: Vec<String>,
+
+    /// Total error budgets to sweep, added to the spec's, e.g. 1e-3,1e-4
+    #[arg(long, value_delimiter = ',')]
+    error_budgets: Vec<f64>,
+
+    /// Threads to estimate on; defaults to the available parallelism
+    #[arg(long)]
+    threads: Option<usize>,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// The program before the change
+    before: PathBuf,
+
+    /// The program after the change; without it, compare hardware profiles with --after-hardware-profile
+    #[arg(required_unless_present = "after_hardware_profile")]
+    after: Option<PathBuf>,
+
+    /// Expression to estimate instead of the @EntryPoint() operation, e.g. "Sample.QFT(64)"
+    #[arg(long)]
+    entry: Option<String>,
+
+    /// Target profile to compile the programs for: base, adaptive_ri or unrestricted
+    #[arg(long, default_value_t = TargetProfile::default())]
+    profile: TargetProfile,
+
+    /// Hardware profile from the profile registry to compare the program on, against --hardware-profile
+    #[arg(long, conflicts_with = "after")]
+    after_hardware_profile: Option<String>,
+
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+#[derive(Args)]
+struct RevisionsArgs {
+    /// Revision before the change, e.g. the pull request's target branch
+    base: String,
+
+    /// Revision after the change
+    #[arg(default_value = "HEAD")]
+    head: String,
+
+    /// The git repository
+    #[arg(long, default_value = ".")]
+    repo: PathBuf,
+
+    /// Target profile to compile the files for: base, adaptive_ri or unrestricted
+    #[arg(long, default_value_t = TargetProfile::default())]
+    profile: TargetProfile,
+
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+#[derive(Args)]
+struct ThresholdArgs {
+    /// Relative increase tolerated for every count before it is flagged, e.g. 0.05 for 5%
+    #[arg(long, default_value_t = 0.0)]
+    threshold: f64,
+
+    /// Relative increase tolerated for one count, e.g. physicalQubits=0.1; may be repeated
+    #[arg(long = "metric-threshold", value_parser = parse_metric_threshold)]
+    metric_thresholds: Vec<(String, f64)>,
+}
+
+#[derive(Args)]
+struct ConfigArgs {
+    /// Configuration file (TOML or JSON) to estimate under; it can name a hardware profile with `hardware = "..."`
+    #[arg(long = "config")]
+    config_file: Option<PathBuf>,
+
+    /// Named hardware profile from the profile registry to estimate on
+    #[arg(long)]
+    hardware_profile: Option<String>,
+
+    /// Profile registry file (TOML or JSON) with the hardware profiles; defaults to $QRE_PROFILE_REGISTRY
+    #[arg(long)]
+    registry: Option<PathBuf>,
+
+    /// Total error budget, split by the estimator between logical errors, T states and rotations
+    #[arg(long, conflicts_with = "error_budget_logical")]
+    error_budget: Option<f64>,
+
+    /// Error budget for logical errors, given together with the T-state and rotation budgets
+    #[arg(long, requires_all = ["error_budget_tstates", "error_budget_rotations"])]
+    error_budget_logical: Option<f64>,
+
+    /// Error budget for T-state distillation
+    #[arg(long, requires = "error_budget_logical")]
+    error_budget_tstates: Option<f64>,
+
+    /// Error budget for rotation synthesis
+    #[arg(long, requires = "error_budget_logical")]
+    error_budget_rotations: Option<f64>,
+}
+
+/// Limits the estimate has to stay within, on top of the program's `// @budget` annotations;
+/// exceeding one fails the run.
+#[derive(Args)]
+struct BudgetArgs {
+    /// Budget file (TOML or JSON) with the most each count may reach, e.g. physicalQubits = 2e6
+    #[arg(long)]
+    budget: Option<PathBuf>,
+
+    /// Most physical qubits the estimate may need
+    #[arg(long)]
+    max_physical_qubits: Option<u64>,
+
+    /// Longest runtime the estimate may take, with unit, e.g. "1 s"
+    #[arg(long)]
+    max_runtime: Option<String>,
+
+    /// Most T gates the program may use
+    #[arg(long)]
+    max_t_count: Option<u64>,
+}
+
+#[derive(Args)]
+struct ModeArgs {
+    /// Estimate the qubits/runtime frontier instead of a single point; budgets are not checked
+    /// against a frontier
+    #[arg(long, conflicts_with = "BudgetArgs")]
+    frontier: bool,
+
+    /// Write the frontier points to this CSV file
+    #[arg(long, requires = "frontier")]
+    csv: Option<PathBuf>,
+
+    /// Rank the qubit and QEC parameters by how much the estimate changes with each; budgets
+    /// are checked against the unperturbed estimate
+    #[arg(long, conflicts_with = "frontier")]
+    sensitivity: bool,
+
+    /// Relative change each parameter is perturbed by for --sensitivity
+    #[arg(long, requires = "sensitivity", default_value_t = qcore::sensitivity::DEFAULT_STEP)]
+    sensitivity_step: f64,
+
+    /// Print the result as json or yaml (the estimator's complete output, pretty-printed) or as
+    /// csv, markdown or table (the formatted physical counts) instead of the estimator's raw
+    /// JSON on one line; other messages then go to stderr
+    #[arg(long, conflicts_with = "sensitivity")]
+    format: Option<OutputFormat>,
+
+    /// Write the formatted result to this file instead of stdout
+    #[arg(long, requires = "format")]
+    output: Option<PathBuf>,
+}
+
+impl ModeArgs {
+    /// Prints or writes `rendered`, the result in the requested format.
+    fn emit(&self, rendered: &str) -> Result<()> {
+        let Some(output) = &self.output else {
+            print!("{rendered}");
+            return Ok(());
+        };
+        fs::write(output, rendered)
+            .into_diagnostic()
+            .with_context(|| format!("could not write `{}`", output.display()))?;
+        eprintln!("Wrote the {} result to {:?}", self.format.unwrap_or_default(), output);
+        Ok(())
+    }
+}
+
+/// What to estimate: a Q# program or an algorithm's known logical counts.
+enum Input {
+    Program(Program),
+    Counts(LogicalCounts),
 }
 
-fn main() -> Result<()> {
+impl CountsArgs {
+    fn logical_counts(&self) -> LogicalCounts {
+        LogicalCounts {
+            num_qubits: self.num_qubits,
+            t_count: self.t_count,
+            rotation_count: self.rotation_count,
+            rotation_depth: self.rotation_depth,
+            ccz_count: self.ccz_count,
+            ccix_count: self.ccix_count,
+            measurement_count: self.measurement_count,
+        }
+    }
+}
+
+impl SweepArgs {
+    fn spec(&self) -> Result<SweepSpec> {
+        let mut spec: SweepSpec = match &self.spec {
+            Some(path) => SweepSpec::load(path)?,
+            None => SweepSpec::default(),
+        };
+        spec.qubit_params.extend(self.qubit_params.iter().map(QubitParams::preset));
+        spec.qec_schemes.extend(self.qec_schemes.iter().map(QecScheme::preset));
+        spec.error_budgets.extend(self.error_budgets.iter().copied().map(ErrorBudget::Total));
+        if let Some(profile) = self.profile {
+            spec.profile = profile;
+        }
+        if self.threads.is_some() {
+            spec.threads = self.threads;
+        }
+        Ok(spec)
+    }
+}
+
+impl BudgetArgs {
+    fn budget(&self) -> Result<Budget> {
+        let mut budget = match &self.budget {
+            Some(path) => Budget::load(path)?,
+            None => Budget::default(),
+        };
+        let flags = [
+            ("physicalQubits", self.max_physical_qubits.map(|max| max.to_string())),
+            ("runtime", self.max_runtime.clone()),
+            ("tCount", self.max_t_count.map(|max| max.to_string())),
+        ];
+        for (metric, max) in flags {
+            if let Some(max) = max {
+                budget.add(metric, &max).map_err(miette::Error::msg)?;
+            }
+        }
+        Ok(budget)
+    }
+}
+
+impl ThresholdArgs {
+    fn thresholds(&self) -> Thresholds {
+        Thresholds {
+            default: self.threshold,
+            metrics: self.metric_thresholds.iter().cloned().collect(),
+        }
+    }
+}
+
+impl ConfigArgs {
+    fn error_budget(&self) -> Option<ErrorBudget> {
+        match (self.error_budget, self.error_budget_logical, self.error_budget_tstates, self.error_budget_rotations) {
+            (Some(total), ..) => Some(ErrorBudget::Total(total)),
+            (None, Some(logical), Some(t_states), Some(rotations)) => Some(ErrorBudget::Partitioned {
+                logical,
+                t_states,
+                rotations,
+            }),
+            _ => None,
+        }
+    }
+
+    fn estimation_config(&self) -> Result<EstimationConfig> {
+        self.estimation_config_on(self.hardware_profile.as_deref())
+    }
+
+    /// The configuration of the config file, or the default one, on `hardware_profile` and with
+    /// the error budget given on the command line.
+    fn estimation_config_on(&self, hardware_profile: Option<&str>) -> Result<EstimationConfig> {
+        let mut config = match (&self.config_file, hardware_profile) {
+            (Some(path), _) => {
+                let mut config = EstimationConfig::load(path)?;
+                config.hardware = hardware_profile.map(str::to_string).or(config.hardware);
+                match config.hardware {
+                    Some(_) => self.registry()?.resolve(config)?,
+                    None => config,
+                }
+            }
+            (None, Some(name)) => self.registry()?.estimation_config(name)?,
+            (None, None) => default_estimation_config(),
+        };
+        if let Some(error_budget) = self.error_budget() {
+            config.params.error_budget = Some(error_budget);
+        }
+        Ok(config)
+    }
+
+    fn registry(&self) -> Result<Registry> {
+        match &self.registry {
+            Some(path) => Ok(Registry::load(path)?),
+            None => Registry::from_env()?.ok_or_else(|| {
+                miette::Error::msg(format!(
+                    "hardware profiles need a profile registry: pass --registry or set {}",
+                    qcore::registry::REGISTRY_ENV
+                ))
+            }),
+        }
+    }
+}
+
+fn main() -> Result<ExitCode> {
     let args = Cli::parse();
-    println!("Reading path from: {:?}", args.path);
 
-    match estimate(&args.path) {
-        Ok(estimate) => println!("Estimation result: {}", estimate),
-        Err(error) => eprintln!("Error: {}", error),
+    let cache = match &args.cache {
+        Some(directory) => Some(ResultCache::on_disk(directory, args.cache_ttl.map(Duration::from_secs))?),
+        None => None,
+    };
+
+    let (input, config, budget_args, mode) = match args.command {
+        Some(Command::Counts { counts, config, budget, mode }) => {
+            (Input::Counts(counts.logical_counts()), config, budget, mode)
+        }
+        Some(Command::Sweep(sweep)) => return run_sweep(&sweep),
+        Some(Command::Diff(diff)) => return run_diff(&diff),
+        Some(Command::DiffRevisions(revisions)) => return run_diff_revisions(&revisions),
+        Some(Command::Presets { json }) => {
+            print_presets(json)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        None => {
+            let path = args.path.expect("clap requires a path without a subcommand");
+            if args.mode.format.is_some() {
+                eprintln!("Reading path from: {:?}", path);
+            } else {
+                println!("Reading path from: {:?}", path);
+            }
+            let program = Program::from_path(&path)?.with_entry(args.entry);
+            (Input::Program(program), args.config, args.budget, args.mode)
+        }
+    };
+    let mut config = config.estimation_config()?;
+    config.profile = args.profile;
+    // Limits given on the command line take precedence over those annotated in the program.
+    // Malformed annotations fail the budget check rather than the run, as the API reports them.
+    let (mut budget, malformed) = match &input {
+        Input::Program(program) => Budget::read_annotations(program),
+        Input::Counts(_) => (Budget::default(), Vec::new()),
+    };
+    budget.merge(budget_args.budget()?);
+    let budget = Annotated { budget, malformed };
+
+    if mode.frontier {
+        let frontier = estimate_frontier_input(&input, &config)?;
+        match mode.format {
+            Some(format) => mode.emit(&qcore::format::render_frontier(&frontier, format)?)?,
+            None => println!("Frontier result: {}", frontier.raw_json()),
+        }
+        if let Some(csv) = &mode.csv {
+            fs::write(csv, frontier.to_csv())
+                .into_diagnostic()
+                .with_context(|| format!("could not write `{}`", csv.display()))?;
+            eprintln!("Wrote {} frontier points to {:?}", frontier.frontier_entries.len(), csv);
+        }
+        if !budget.is_empty() {
+            eprintln!("The program's budget annotations are not checked against a frontier; estimate without --frontier to check them.");
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if mode.sensitivity {
+        let sensitivity = match &input {
+            Input::Program(program) => estimate_program_sensitivity(program, &config, mode.sensitivity_step)?,
+            Input::Counts(counts) => estimate_logical_counts_sensitivity(counts, &config, mode.sensitivity_step)?,
+        };
+        print_sensitivity(&sensitivity);
+        return Ok(budget.check(&sensitivity.baseline, |message| println!("{message}")));
     }
 
+    let estimate = estimate_input(&input, &config, cache.as_ref())?;
+    // With a format, stdout holds only the result so it can be piped into other tools.
+    let report = |message: String| {
+        if mode.format.is_some() {
+            eprintln!("{message}");
+        } else {
+            println!("{message}");
+        }
+    };
+    match mode.format {
+        Some(format) => {
+            if estimate.cache_hit {
+                eprintln!("Estimation result from cache");
+            }
+            mode.emit(&qcore::format::render_estimate(&estimate, format)?)?;
+        }
+        None if estimate.cache_hit => println!("Estimation result (from cache): {}", estimate.raw_json()),
+        None => println!("Estimation result: {}", estimate.raw_json()),
+    }
+
+    Ok(budget.check(&estimate, report))
+}
+
+/// The limits to check an estimate against, and the budget annotations that could not be read.
+struct Annotated {
+    budget: Budget,
+    malformed: Vec<qcore::Error>,
+}
+
+impl Annotated {
+    fn is_empty(&self) -> bool {
+        self.budget.is_empty() && self.malformed.is_empty()
+    }
+
+    /// Reports every limit against `estimate`, and every malformed annotation as a failed
+    /// check, failing the run if a limit is exceeded or an annotation malformed.
+    fn check(&self, estimate: &EstimationOutput, report: impl Fn(String)) -> ExitCode {
+        let checks = self.budget.check(estimate);
+        let exceeded = checks.iter().filter(|check| !check.passed()).count();
+        match exceeded {
+            _ if checks.is_empty() => {}
+            0 => report(format!("Within all {} budget limits:", checks.len())),
+            exceeded => report(format!("Over budget on {exceeded} of {} limits:", checks.len())),
+        }
+        for check in &checks {
+            report(format!("  {} {check}", if check.passed() { "passed" } else { "FAILED" }));
+        }
+
+        if !self.malformed.is_empty() {
+            report(format!("{} budget annotations could not be read:", self.malformed.len()));
+        }
+        for error in &self.malformed {
+            let location = error.diagnostics().into_iter().find_map(|diagnostic| diagnostic.locations.into_iter().next());
+            match location {
+                Some(location) => report(format!(
+                    "  FAILED {}:{}:{}: {error}",
+                    location.file.as_deref().unwrap_or_default(),
+                    location.line,
+                    location.column
+                )),
+                None => report(format!("  FAILED {error}")),
+            }
+        }
+
+        if exceeded > 0 || !self.malformed.is_empty() { ExitCode::from(OVER_BUDGET) } else { ExitCode::SUCCESS }
+    }
+}
+
+fn run_sweep(args: &SweepArgs) -> Result<ExitCode> {
+    let spec = args.spec()?;
+    let program = Program::from_path(&args.path)?.with_entry(args.entry.clone());
+
+    // Points are printed as they finish, so their order varies between runs.
+    let (mut done, mut failures) = (0, 0);
+    let total = spec.size().ok_or_else(|| miette::Error::msg("the sweep has too many points to estimate"))?;
+    estimate_program_sweep(&program, &spec, |point| {
+        done += 1;
+        match point.output {
+            Ok(output) => println!(
+                "[{done}/{total}] #{} {}: {} physical qubits, {} runtime",
+                point.index,
+                point.config.label,
+                output.physical_counts_formatted.physical_qubits,
+                output.physical_counts_formatted.runtime
+            ),
+            Err(error) => {
+                println!("[{done}/{total}] #{} {}: {error}", point.index, point.config.label);
+                failures += 1;
+            }
+        }
+    })?;
+
+    if failures > 0 {
+        println!("{failures} of {total} points could not be estimated.");
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_diff(args: &DiffArgs) -> Result<ExitCode> {
+    let thresholds = args.thresholds.thresholds();
+    let mut config = args.config.estimation_config()?;
+    config.profile = args.profile;
+    let before = Program::from_path(&args.before)?.with_entry(args.entry.clone());
+
+    let diff = match &args.after {
+        Some(after) => {
+            println!("Comparing {:?} with {:?}", args.before, after);
+            let after = Program::from_path(after)?.with_entry(args.entry.clone());
+            estimate_program_diff(&before, &after, &config, &thresholds)?
+        }
+        None => {
+            let mut after_config = args.config.estimation_config_on(args.after_hardware_profile.as_deref())?;
+            after_config.profile = args.profile;
+            println!("Comparing `{}` with `{}`", config.label, after_config.label);
+            estimate_config_diff(&before, &config, &after_config, &thresholds)?
+        }
+    };
+    print_diff(&diff);
+
+    Ok(if diff.has_regressions() { ExitCode::from(OVER_BUDGET) } else { ExitCode::SUCCESS })
+}
+
+fn print_diff(diff: &EstimationDiff) {
+    println!("  {:<28} {:>16} {:>16} {:>16} {:>10}", "count", "before", "after", "delta", "relative");
+    for delta in &diff.deltas {
+        let relative = match delta.relative {
+            Some(relative) => format!("{:+.2}%", relative * 100.0),
+            None if delta.after == 0 => String::new(),
+            None => "new".to_string(),
+        };
+        println!(
+            "  {:<28} {:>16} {:>16} {:>+16} {:>10}{}",
+            delta.metric,
+            delta.before,
+            delta.after,
+            delta.absolute,
+            relative,
+            if delta.regression { "  regression" } else { "" }
+        );
+    }
+
+    match diff.regressions().count() {
+        0 => println!("No count grew beyond its threshold."),
+        1 => println!("1 count grew beyond its threshold."),
+        regressions => println!("{regressions} counts grew beyond their thresholds."),
+    }
+}
+
+fn run_diff_revisions(args: &RevisionsArgs) -> Result<ExitCode> {
+    let mut config = args.config.estimation_config()?;
+    config.profile = args.profile;
+    let files = qcore::git::estimate_revisions(&args.repo, &args.base, &args.head, &config, &args.thresholds.thresholds())?;
+    if files.is_empty() {
+        println!("No Q# programs with an entry point changed between {} and {}.", args.base, args.head);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    println!("Q# programs changed between {} and {}, estimated under `{}`:", args.base, args.head, config.label);
+    println!("  {:<40} {:<9} {:<36} {:<36}", "file or project", "change", "physical qubits", "runtime");
+    let (mut regressions, mut failures) = (0, 0);
+    for file in &files {
+        let change = match file.change {
+            FileChange::Added => "added",
+            FileChange::Modified => "modified",
+            FileChange::Deleted => "deleted",
+        };
+        match &file.estimate {
+            Ok(FileEstimate::Added(output) | FileEstimate::Deleted(output)) => println!(
+                "  {:<40} {:<9} {:<36} {:<36}",
+                file.path, change, output.physical_counts_formatted.physical_qubits, output.physical_counts_formatted.runtime
+            ),
+            Ok(FileEstimate::Modified(diff)) => {
+                let describe = |metric: &str, before: &str, after: &str| {
+                    let relative = diff
+                        .deltas
+                        .iter()
+                        .find(|delta| delta.metric == metric)
+                        .and_then(|delta| delta.relative)
+                        .map(|relative| format!(" ({:+.2}%)", relative * 100.0))
+                        .unwrap_or_default();
+                    format!("{before} → {after}{relative}")
+                };
+                let (before, after) = (&diff.before.physical_counts_formatted, &diff.after.physical_counts_formatted);
+                println!(
+                    "  {:<40} {:<9} {:<36} {:<36}{}",
+                    file.path,
+                    change,
+                    describe("physicalQubits", &before.physical_qubits, &after.physical_qubits),
+                    describe("runtime", &before.runtime, &after.runtime),
+                    if diff.has_regressions() { "  regression" } else { "" }
+                );
+                if diff.has_regressions() {
+                    regressions += 1;
+                }
+            }
+            Err(error) => {
+                println!("  {:<40} {:<9} could not estimate: {error}", file.path, change);
+                failures += 1;
+            }
+        }
+    }
+
+    match regressions {
+        0 => println!("No changed program grew beyond the thresholds."),
+        1 => println!("1 changed program grew beyond the thresholds."),
+        regressions => println!("{regressions} changed programs grew beyond the thresholds."),
+    }
+
+    // A program that no longer compiles is worse than one that got more expensive.
+    Ok(if failures > 0 {
+        ExitCode::FAILURE
+    } else if regressions > 0 {
+        ExitCode::from(OVER_BUDGET)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Parses `metric=threshold`, e.g. `physicalQubits=0.1`.
+fn parse_metric_threshold(value: &str) -> std::result::Result<(String, f64), String> {
+    let (metric, threshold) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected METRIC=THRESHOLD, e.g. physicalQubits=0.1, got `{value}`"))?;
+    let threshold = threshold
+        .parse()
+        .map_err(|_| format!("the threshold of `{metric}` is not a number: `{threshold}`"))?;
+    Ok((metric.to_string(), threshold))
+}
+
+fn print_sensitivity(sensitivity: &Sensitivity) {
+    let counts = &sensitivity.baseline.physical_counts_formatted;
+    println!("Baseline: {} physical qubits, {} runtime", counts.physical_qubits, counts.runtime);
+    println!(
+        "Elasticities for a change of ±{}% in each parameter, strongest first:",
+        sensitivity.step * 100.0
+    );
+    println!("  {:<46} {:<28} {:>15} {:>10}", "parameter", "value", "physical qubits", "runtime");
+    for elasticity in &sensitivity.elasticities {
+        println!(
+            "  {:<46} {:<28} {:>15.3} {:>10.3}",
+            elasticity.parameter, elasticity.value, elasticity.physical_qubits, elasticity.runtime
+        );
+    }
+    for (parameter, message) in &sensitivity.failures {
+        println!("  {parameter}: could not estimate the perturbed values: {message}");
+    }
+}
+
+fn print_presets(json: bool) -> Result<()> {
+    let catalog = qcore::catalog::catalog()?;
+    if json {
+        println!("{}", catalog.to_json());
+        return Ok(());
+    }
+
+    println!("Qubit models:");
+    for preset in &catalog.qubit_params {
+        println!("  {:<18} {:<10} {}", preset.params.name, preset.params.instruction_set, preset.description);
+    }
+    println!("QEC schemes:");
+    for preset in &catalog.qec_schemes {
+        println!("  {:<18} {:<10} {}", preset.params.name, format!("{:?}", preset.instruction_set), preset.description);
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn estimate_input(input: &Input, config: &EstimationConfig, cache: Option<&ResultCache>) -> qcore::Result<EstimationOutput> {
+    match (input, cache) {
+        (Input::Program(program), Some(cache)) => cache.estimate(program, config),
+        (Input::Program(program), None) => estimate_program(program, config),
+        (Input::Counts(counts), _) => estimate_logical_counts(counts, config),
+    }
+}
+
+fn estimate_frontier_input(input: &Input, config: &EstimationConfig) -> qcore::Result<FrontierOutput> {
+    match input {
+        Input::Program(program) => estimate_program_frontier(program, config),
+        Input::Counts(counts) => estimate_frontier_logical_counts(counts, config),
+    }
+}
@@ -0,0 +1,71 @@
+//! Measures how much of an estimation is compiling the standard library, which every
+//! estimation does again (see `build_interpreter`), back to back and with one estimation
+//! per available core at once, as under concurrent requests to the server. Run with
+//! `cargo bench -p qcore`.
+
+use std::thread;
+use std::time::{Duration, Instant};
+use qcore::{default_estimation_config, estimate_program, Program, TargetProfile};
+use qsc::target::Profile;
+
+const SOURCE: &str = "namespace Bench {
+    @EntryPoint()
+    operation Main() : Unit {
+        use qs = Qubit[3];
+        for q in qs {
+            T(q);
+        }
+        CCNOT(qs[0], qs[1], qs[2]);
+        ResetAll(qs);
+    }
+}";
+
+const RUNS: u32 = 10;
+
+fn main() {
+    let capabilities = qsc::TargetCapabilityFlags::from(Profile::from(TargetProfile::default()));
+    let stdlib = average(|| {
+        let start = Instant::now();
+        qsc::compile::package_store_with_stdlib(capabilities);
+        start.elapsed()
+    });
+    println!("compile the standard library:             {stdlib:?}");
+
+    let config = default_estimation_config();
+    let program = Program::from_source("bench.qs".into(), SOURCE.into());
+    let estimate = || {
+        let start = Instant::now();
+        estimate_program(&program, &config).expect("the benchmark program is estimated");
+        start.elapsed()
+    };
+
+    let back_to_back = average(estimate);
+    println!("estimate, back to back:                   {back_to_back:?}");
+    println!(
+        "standard library share:                   {:.0}%",
+        100.0 * stdlib.as_secs_f64() / back_to_back.as_secs_f64()
+    );
+
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let start = Instant::now();
+    let latencies: Vec<Duration> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| scope.spawn(|| (0..RUNS).map(|_| estimate()).collect::<Vec<_>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("the benchmark threads do not panic"))
+            .collect()
+    });
+    let elapsed = start.elapsed();
+    let latency = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+    println!("estimate, {threads} at once:  {latency:?} each");
+    println!(
+        "throughput, {threads} at once: {:.1} estimations/s",
+        latencies.len() as f64 / elapsed.as_secs_f64()
+    );
+}
+
+fn average(mut run: impl FnMut() -> Duration) -> Duration {
+    (0..RUNS).map(|_| run()).sum::<Duration>() / RUNS
+}
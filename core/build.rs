@@ -0,0 +1,71 @@
+//! Records which qsharp checkout qcore is built against as `QSHARP_VERSION`, so cached
+//! estimation results are keyed by the compiler and estimator that produced them. A
+//! checkout's version is `0.0.0` between releases, so its git commit is added when there
+//! is one.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const QSHARP: &str = "../qsharp";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let git_dirs = git_dirs();
+    let watched = ["resource_estimator/Cargo.toml", "Cargo.toml"].map(|path| Path::new(QSHARP).join(path));
+    let git_files = git_dirs.iter().flat_map(|dir| ["HEAD", "refs", "packed-refs"].map(|path| dir.join(path)));
+    for path in watched.into_iter().chain(git_files) {
+        // Cargo reruns the script on every build for paths that do not exist.
+        if path.exists() {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+
+    let version = ["resource_estimator/Cargo.toml", "Cargo.toml"]
+        .into_iter()
+        .find_map(|manifest| manifest_version(&Path::new(QSHARP).join(manifest)))
+        .unwrap_or_else(|| "unknown".to_string());
+    let commit = if git_dirs.is_empty() { None } else { git(&["rev-parse", "HEAD"]) };
+
+    match commit {
+        Some(commit) => println!("cargo:rustc-env=QSHARP_VERSION={version}+{commit}"),
+        None => println!("cargo:rustc-env=QSHARP_VERSION={version}"),
+    }
+}
+
+/**
+    The checkout's git directory and, for a worktree, the common one its refs are in. In a
+    submodule or worktree `.git` is a file pointing elsewhere, so git is asked where they are.
+    Empty unless the checkout is a repository of its own; inside another repository HEAD
+    would be that repository's.
+**/
+fn git_dirs() -> Vec<PathBuf> {
+    if !Path::new(QSHARP).join(".git").exists() {
+        return Vec::new();
+    }
+    let Some(output) = git(&["rev-parse", "--git-dir", "--git-common-dir"]) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = output.lines().map(|dir| Path::new(QSHARP).join(dir)).collect();
+    dirs.dedup();
+    dirs
+}
+
+/// The trimmed output of a git command run in the checkout, if it succeeds.
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(QSHARP).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The first literal `version = "..."` in a manifest; `version.workspace = true` is skipped
+/// so the workspace manifest is read next.
+fn manifest_version(manifest: &Path) -> Option<String> {
+    let contents = fs::read_to_string(manifest).ok()?;
+    contents.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("version")?.trim_start().strip_prefix('=')?.trim();
+        Some(value.strip_prefix('"')?.strip_suffix('"')?.to_string())
+    })
+}
@@ -0,0 +1,410 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use serde::Deserialize;
+
+use crate::config::nanoseconds;
+use crate::diff::METRICS;
+use crate::{load_toml_or_json, Error, EstimationOutput, Program, Result, SourceLocation};
+
+/// Marks a budget annotation in a Q# comment.
+const ANNOTATION: &str = "@budget";
+
+/**
+    Upper limits on the counts of an estimate, by the count names diffs use, read from a
+    TOML (`.toml`) or JSON file:
+
+    ```toml
+    physicalQubits = 2e6
+    runtime = "1 s"
+    tCount = 1_000_000
+    ```
+
+    or from annotations in the program's own sources, right above the callable they limit:
+
+    ```qsharp
+    // @budget physicalQubits <= 2e6
+    // @budget runtime <= 10 ms
+    @EntryPoint()
+    operation Main() : Unit { ... }
+    ```
+
+    The runtime takes a time with unit (`ns`, `µs`, `ms`, `s`) or plain nanoseconds.
+**/
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    pub limits: Vec<Limit>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Limit {
+    pub metric: &'static str,
+    /// In the count's unit; nanoseconds for the runtime.
+    pub max: f64,
+    /// Where the limit is annotated, for limits from the program's sources.
+    pub location: Option<SourceLocation>,
+}
+
+/**
+    A count of an estimate against its limit.
+**/
+#[derive(Clone, Debug)]
+pub struct LimitCheck {
+    pub limit: Limit,
+    pub actual: u64,
+}
+
+/// A limit as written in a budget file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawLimit {
+    Number(f64),
+    Text(String),
+}
+
+impl Budget {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let invalid = |message: String| Error::Config {
+            label: path.display().to_string(),
+            message,
+        };
+        let limits: BTreeMap<String, RawLimit> = load_toml_or_json(path, invalid)?;
+
+        let mut budget = Budget::default();
+        for (metric, limit) in limits {
+            let limit = match limit {
+                RawLimit::Number(max) => max.to_string(),
+                RawLimit::Text(max) => max,
+            };
+            budget.add(&metric, &limit).map_err(invalid)?;
+        }
+        Ok(budget)
+    }
+
+    /// The limits annotated with `// @budget <count> <= <limit>` comments on the callable the
+    /// program estimates: its `@EntryPoint()`, or the callable its entry expression calls.
+    /// Each annotation belongs to the operation or function declared right after it.
+    pub fn from_annotations(program: &Program) -> Result<Self> {
+        let (budget, mut errors) = Self::read_annotations(program);
+        match errors.is_empty() {
+            true => Ok(budget),
+            false => Err(errors.swap_remove(0)),
+        }
+    }
+
+    /// Like `from_annotations`, reading past malformed annotations, which are returned as
+    /// errors next to the limits of the well-formed ones.
+    pub fn read_annotations(program: &Program) -> (Self, Vec<Error>) {
+        annotations(&program.package_graph_sources.root.sources, program.entry.as_deref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+    }
+
+    /// Adds every limit of `other`, replacing the limits on the same counts, e.g. to let
+    /// command line limits override annotated ones.
+    pub fn merge(&mut self, other: Budget) {
+        for limit in other.limits {
+            self.limits.retain(|existing| existing.metric != limit.metric);
+            self.limits.push(limit);
+        }
+    }
+
+    /// Adds the limit `max` on `metric`, e.g. `"2e6"` physical qubits or a `"1 s"` runtime;
+    /// a later limit on the same count replaces the earlier one.
+    pub fn add(&mut self, metric: &str, max: &str) -> std::result::Result<(), String> {
+        self.add_at(metric, max, None)
+    }
+
+    fn add_at(&mut self, metric: &str, max: &str, location: Option<SourceLocation>) -> std::result::Result<(), String> {
+        self.push(parse_limit(metric, max, location)?);
+        Ok(())
+    }
+
+    fn push(&mut self, limit: Limit) {
+        self.limits.retain(|existing| existing.metric != limit.metric);
+        self.limits.push(limit);
+    }
+
+    /// Every limit against the estimate, in the order they were added.
+    pub fn check(&self, output: &EstimationOutput) -> Vec<LimitCheck> {
+        self.limits
+            .iter()
+            .filter_map(|limit| {
+                let (_, count) = METRICS.iter().find(|(name, _)| *name == limit.metric)?;
+                Some(LimitCheck {
+                    limit: limit.clone(),
+                    actual: count(output),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A limit on `metric`, e.g. `"2e6"` physical qubits or a `"1 s"` runtime.
+fn parse_limit(metric: &str, max: &str, location: Option<SourceLocation>) -> std::result::Result<Limit, String> {
+    let Some(&(metric, _)) = METRICS.iter().find(|(name, _)| *name == metric) else {
+        let names: Vec<&str> = METRICS.iter().map(|(name, _)| *name).collect();
+        return Err(format!("unknown count `{metric}` in budget, expected one of {}", names.join(", ")));
+    };
+
+    let value = match max.trim().parse::<f64>() {
+        Ok(value) => Some(value),
+        Err(_) if metric == "runtime" => nanoseconds(max.trim()),
+        Err(_) => None,
+    };
+    let max = value.filter(|value| *value >= 0.0).ok_or_else(|| {
+        let expected = if metric == "runtime" { "a time with unit, e.g. \"1 s\"" } else { "a number" };
+        format!("the budget of `{metric}` must be {expected}, got `{max}`")
+    })?;
+
+    Ok(Limit { metric, max, location })
+}
+
+/**
+    Reads the budget annotations in `sources`, keeping the limits on the callable `entry`
+    calls, or on the `@EntryPoint()` without one. An annotation belongs to the operation or
+    function declared next, past blank lines, comments and attributes; any other code in
+    between is an error, as is an annotation that does not parse. Declarations are found
+    line by line, so a callable declared across lines must have its name on the first.
+**/
+fn annotations(sources: &[(Arc<str>, Arc<str>)], entry: Option<&str>) -> (Budget, Vec<Error>) {
+    let estimated = entry.map(|entry| (entry, called(entry)));
+    let mut budget = Budget::default();
+    let mut errors = Vec::new();
+
+    for (name, contents) in sources {
+        let error = |message: String, span: (usize, usize)| Error::Annotation {
+            message,
+            source_code: miette::NamedSource::new(name.as_ref(), contents.to_string()),
+            span: span.into(),
+        };
+        // The annotations since the last declaration, and whether an `@EntryPoint()` is among
+        // the attributes since.
+        let mut pending: Vec<(Limit, (usize, usize))> = Vec::new();
+        let mut entry_point = false;
+        let mut offset = 0;
+        for (index, line) in contents.split_inclusive('\n').enumerate() {
+            let line_offset = offset;
+            offset += line.len();
+
+            let comment = line.find("//");
+            let mut code = comment.map_or(line, |comment| &line[..comment]).trim();
+            if let Some(at) = comment.and_then(|comment| annotation_at(line, comment)) {
+                let annotation = line[at..].trim_end();
+                let span = (line_offset + at, annotation.len());
+                let location = SourceLocation {
+                    file: Some(name.to_string()),
+                    line: index + 1,
+                    column: line[..at].chars().count() + 1,
+                    label: Some(annotation.to_string()),
+                };
+                let parsed = annotation[ANNOTATION.len()..]
+                    .split_once("<=")
+                    .ok_or_else(|| format!("expected `{ANNOTATION} <count> <= <limit>`, e.g. `{ANNOTATION} physicalQubits <= 2e6`"))
+                    .and_then(|(metric, max)| parse_limit(metric.trim(), max.trim(), Some(location)));
+                match parsed {
+                    Ok(limit) => pending.push((limit, span)),
+                    Err(message) => errors.push(error(message, span)),
+                }
+            }
+
+            while let Some(attribute) = code.strip_prefix('@') {
+                entry_point |= attribute.starts_with("EntryPoint");
+                code = attribute.split_once(')').map_or("", |(_, rest)| rest).trim_start();
+            }
+            if code.is_empty() {
+                continue;
+            }
+
+            let declared = declared(code);
+            for (limit, span) in pending.drain(..) {
+                let Some(callable) = declared else {
+                    let message = "expected the operation or function it limits to be declared next".to_string();
+                    errors.push(error(message, span));
+                    continue;
+                };
+                match estimated {
+                    None if entry_point => budget.push(limit),
+                    Some((_, Some(called))) if called == callable => budget.push(limit),
+                    Some((entry, None)) => {
+                        let message = format!("cannot tell which callable the entry expression `{entry}` estimates");
+                        errors.push(error(message, span));
+                    }
+                    _ => {}
+                }
+            }
+            entry_point = false;
+        }
+
+        for (_, span) in pending {
+            let message = "expected the operation or function it limits to be declared next".to_string();
+            errors.push(error(message, span));
+        }
+    }
+    (budget, errors)
+}
+
+/// Where the annotation in the comment starting at `comment` of `line` is, if it has one.
+fn annotation_at(line: &str, comment: usize) -> Option<usize> {
+    let at = comment + line[comment..].find(ANNOTATION)?;
+    line[comment..at].trim_start_matches('/').trim().is_empty().then_some(at)
+}
+
+/// The name of the operation or function `code` declares, e.g. `Main` for
+/// `operation Main() : Unit {`.
+fn declared(code: &str) -> Option<&str> {
+    let mut words = code.split_whitespace().skip_while(|word| *word == "internal");
+    if !matches!(words.next()?, "operation" | "function") {
+        return None;
+    }
+    let name = words.next()?;
+    let end = name.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(name.len());
+    Some(&name[..end]).filter(|name| !name.is_empty())
+}
+
+/// The callable an entry expression calls, e.g. `QFT` for `Sample.QFT(64)`, unless it is
+/// anything but one call.
+fn called(entry: &str) -> Option<&str> {
+    let (path, arguments) = entry.trim().split_once('(')?;
+    let is_identifier = |part: &str| {
+        part.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    let path = path.trim();
+    (arguments.ends_with(')') && path.split('.').all(is_identifier)).then(|| path.rsplit('.').next())?
+}
+
+impl LimitCheck {
+    pub fn passed(&self) -> bool {
+        self.actual as f64 <= self.limit.max
+    }
+}
+
+impl std::fmt::Display for LimitCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(location) = &self.limit.location {
+            write!(f, "{}:{}:{}: ", location.file.as_deref().unwrap_or_default(), location.line, location.column)?;
+        }
+
+        let (actual, max) = if self.limit.metric == "runtime" {
+            (describe_nanoseconds(self.actual as f64), describe_nanoseconds(self.limit.max))
+        } else {
+            (self.actual.to_string(), self.limit.max.to_string())
+        };
+        if self.passed() {
+            return write!(f, "{} is {actual}, within the budget of {max}", self.limit.metric);
+        }
+        write!(f, "{} is {actual}, over the budget of {max}", self.limit.metric)?;
+        if self.limit.max > 0.0 {
+            write!(f, " (+{:.1}%)", (self.actual as f64 / self.limit.max - 1.0) * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+fn describe_nanoseconds(nanoseconds: f64) -> String {
+    let (value, unit) = [(1e9, "s"), (1e6, "ms"), (1e3, "µs")]
+        .into_iter()
+        .find(|(scale, _)| nanoseconds >= *scale)
+        .map_or((nanoseconds, "ns"), |(scale, unit)| (nanoseconds / scale, unit));
+    format!("{value:.2} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use super::{annotations, Budget};
+    use crate::Error;
+
+    fn read(source: &str, entry: Option<&str>) -> (Budget, Vec<Error>) {
+        annotations(&[(Arc::from("Main.qs"), Arc::from(source))], entry)
+    }
+
+    fn limits(budget: &Budget) -> Vec<(&str, f64)> {
+        budget.limits.iter().map(|limit| (limit.metric, limit.max)).collect()
+    }
+
+    const SOURCE: &str = "namespace Sample {
+    // @budget physicalQubits <= 2e6
+    // @budget runtime <= 10 ms
+    @EntryPoint()
+    operation Main() : Unit {
+        QFT(4);
+    }
+
+    /// Applies the quantum Fourier transform.
+    // @budget tCount <= 1000
+    internal operation QFT(n : Int) : Unit {}
+}";
+
+    #[test]
+    fn reads_the_limits_of_the_entry_point() {
+        let (budget, errors) = read(SOURCE, None);
+        assert!(errors.is_empty());
+        assert_eq!(limits(&budget), [("physicalQubits", 2e6), ("runtime", 1e7)]);
+
+        let location = budget.limits[0].location.as_ref().unwrap();
+        assert_eq!((location.line, location.column), (2, 8));
+    }
+
+    #[test]
+    fn reads_the_limits_of_the_callable_an_entry_expression_calls() {
+        let (budget, errors) = read(SOURCE, Some("Sample.QFT(64)"));
+        assert!(errors.is_empty());
+        assert_eq!(limits(&budget), [("tCount", 1000.0)]);
+
+        let (budget, errors) = read(SOURCE, Some("Sample.Other()"));
+        assert!(errors.is_empty());
+        assert!(budget.is_empty());
+    }
+
+    #[test]
+    fn rejects_entry_expressions_other_than_one_call() {
+        let (_, errors) = read(SOURCE, Some("{ Sample.QFT(4); Sample.QFT(8) }"));
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].to_string().contains("cannot tell which callable"));
+    }
+
+    #[test]
+    fn reports_malformed_annotations_and_keeps_the_others() {
+        let source = "// @budget physicalQubits 2e6
+// @budget qubits <= 2e6
+// @budget tCount <= many
+// @budget runtime <= 1 s
+@EntryPoint()
+operation Main() : Unit {}";
+        let (budget, errors) = read(source, None);
+        assert_eq!(limits(&budget), [("runtime", 1e9)]);
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].contains("expected `@budget <count> <= <limit>`"));
+        assert!(messages[1].contains("unknown count `qubits`"));
+        assert!(messages[2].contains("must be a number, got `many`"));
+    }
+
+    #[test]
+    fn rejects_annotations_not_followed_by_a_callable() {
+        let source = "// @budget physicalQubits <= 2e6
+namespace Sample {
+    @EntryPoint()
+    operation Main() : Unit {}
+    // @budget tCount <= 10
+}";
+        let (budget, errors) = read(source, None);
+        assert!(budget.is_empty());
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|error| error.to_string().contains("declared next")));
+    }
+
+    #[test]
+    fn ignores_annotations_that_are_not_comments_of_their_own() {
+        let source = "// see @budget physicalQubits <= 1
+@EntryPoint() operation Main() : Unit {} // @budget tCount <= 5";
+        let (budget, errors) = read(source, None);
+        assert!(errors.is_empty());
+        assert_eq!(limits(&budget), [("tCount", 5.0)]);
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+use qsc_project::PackageInfo;
+use sha2::{Digest, Sha256};
+
+use crate::{estimate_program_batch, EstimationConfig, EstimationOutput, Error, Program, Result};
+
+/// Environment variable with the directory the server keeps cached results in.
+pub const CACHE_DIR_ENV: &str = "QRE_CACHE_DIR";
+/// Environment variable with how many seconds the server keeps cached results.
+pub const CACHE_TTL_ENV: &str = "QRE_CACHE_TTL";
+/// Environment variable with how many results the server keeps in memory.
+pub const CACHE_SIZE_ENV: &str = "QRE_CACHE_SIZE";
+
+/// Results kept in memory unless another capacity is given.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/**
+    Estimation results keyed by everything that determines them: the program's sources
+    (with normalized line endings), its entry expression, the target profile and the
+    estimation params. Labels and details only name a configuration and are not part of
+    the key. The most recently used results are kept in memory, up to the capacity, and
+    with a directory all of them on disk as `<key>.json`, so unchanged programs are not
+    estimated again in later runs, e.g. in CI. Results older than the TTL are estimated
+    again; failed estimations are never cached.
+**/
+pub struct ResultCache {
+    memory: Mutex<Memory>,
+    capacity: usize,
+    directory: Option<PathBuf>,
+    ttl: Option<Duration>,
+}
+
+/// Cached results by key, with when each was last used to evict the least recently used.
+#[derive(Default)]
+struct Memory {
+    entries: HashMap<String, Entry>,
+    clock: u64,
+}
+
+struct Entry {
+    stored: SystemTime,
+    used: u64,
+    raw: String,
+}
+
+impl ResultCache {
+    pub fn in_memory(ttl: Option<Duration>) -> Self {
+        ResultCache {
+            memory: Mutex::default(),
+            capacity: DEFAULT_CAPACITY,
+            directory: None,
+            ttl,
+        }
+    }
+
+    /// Keeps at most `capacity` results in memory; 0 keeps none, e.g. to rely on the disk only.
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        ResultCache { capacity, ..self }
+    }
+
+    pub fn on_disk(directory: impl Into<PathBuf>, ttl: Option<Duration>) -> Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory).map_err(|e| Error::cache(&directory, e))?;
+
+        Ok(ResultCache {
+            directory: Some(directory),
+            ..Self::in_memory(ttl)
+        })
+    }
+
+    /// The cache configured by `QRE_CACHE_DIR`, `QRE_CACHE_TTL` and `QRE_CACHE_SIZE`: on disk
+    /// if a directory is set, otherwise in memory only.
+    pub fn from_env() -> Result<Self> {
+        let ttl = env_number(CACHE_TTL_ENV, "a number of seconds")?.map(Duration::from_secs);
+        let capacity = env_number(CACHE_SIZE_ENV, "a number of results")?;
+
+        let cache = match std::env::var_os(CACHE_DIR_ENV) {
+            Some(directory) => Self::on_disk(directory, ttl)?,
+            None => Self::in_memory(ttl),
+        };
+        Ok(cache.with_capacity(capacity.map_or(DEFAULT_CAPACITY, |capacity| capacity as usize)))
+    }
+
+    pub fn estimate(&self, program: &Program, config: &EstimationConfig) -> Result<EstimationOutput> {
+        self.estimate_batch(program, std::slice::from_ref(config))?
+            .pop()
+            .expect("the estimator returns one result per configuration")
+    }
+
+    /// Like `estimate_program_batch`, estimating only the configurations without a cached
+    /// result, still in one batch.
+    pub fn estimate_batch(&self, program: &Program, configs: &[EstimationConfig]) -> Result<Vec<Result<EstimationOutput>>> {
+        let keys: Vec<String> = configs.iter().map(|config| key(program, config)).collect();
+        let mut outputs: Vec<Option<Result<EstimationOutput>>> = keys.iter().map(|key| self.get(key).map(Ok)).collect();
+
+        let misses: Vec<usize> = (0..configs.len()).filter(|&index| outputs[index].is_none()).collect();
+        if !misses.is_empty() {
+            let missed: Vec<EstimationConfig> = misses.iter().map(|&index| configs[index].clone()).collect();
+            for (index, output) in misses.into_iter().zip(estimate_program_batch(program, &missed)?) {
+                if let Ok(output) = &output {
+                    self.put(&keys[index], output.raw_json())?;
+                }
+                outputs[index] = Some(output);
+            }
+        }
+
+        Ok(outputs
+            .into_iter()
+            .map(|output| output.expect("every configuration is looked up or estimated"))
+            .collect())
+    }
+
+    fn get(&self, key: &str) -> Option<EstimationOutput> {
+        let mut memory = self.memory.lock().unwrap_or_else(PoisonError::into_inner);
+        memory.clock += 1;
+        let now = memory.clock;
+        let raw = match memory.entries.get_mut(key) {
+            Some(entry) if self.is_fresh(entry.stored) => {
+                entry.used = now;
+                entry.raw.clone()
+            }
+            _ => {
+                // A missing, stale or unreadable file is just a miss.
+                let path = self.directory.as_ref()?.join(format!("{key}.json"));
+                let stored = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+                if !self.is_fresh(stored) {
+                    return None;
+                }
+                let raw = fs::read_to_string(&path).ok()?;
+                self.remember(&mut memory, key, stored, &raw);
+                raw
+            }
+        };
+
+        let mut output = EstimationOutput::from_json(&raw).ok()?;
+        output.cache_hit = true;
+        Some(output)
+    }
+
+    fn put(&self, key: &str, raw: &str) -> Result<()> {
+        let mut memory = self.memory.lock().unwrap_or_else(PoisonError::into_inner);
+        self.remember(&mut memory, key, SystemTime::now(), raw);
+        drop(memory);
+
+        if let Some(directory) = &self.directory {
+            // Written under a temporary name first, so concurrent readers never see half a file.
+            let path = directory.join(format!("{key}.json"));
+            let mut file = tempfile::NamedTempFile::new_in(directory).map_err(|e| Error::cache(directory, e))?;
+            file.write_all(raw.as_bytes()).map_err(|e| Error::cache(&path, e))?;
+            file.persist(&path).map_err(|e| Error::cache(&path, e.error))?;
+        }
+        Ok(())
+    }
+
+    /// Keeps a result in memory, dropping stale results and then the least recently used
+    /// ones beyond the capacity.
+    fn remember(&self, memory: &mut Memory, key: &str, stored: SystemTime, raw: &str) {
+        memory.entries.retain(|_, entry| self.is_fresh(entry.stored));
+        memory.clock += 1;
+        let used = memory.clock;
+        memory.entries.insert(key.to_string(), Entry { stored, used, raw: raw.to_string() });
+
+        while memory.entries.len() > self.capacity {
+            let oldest = memory
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone())
+                .expect("the cache is over its capacity, so not empty");
+            memory.entries.remove(&oldest);
+        }
+    }
+
+    fn is_fresh(&self, stored: SystemTime) -> bool {
+        self.ttl
+            .is_none_or(|ttl| stored.elapsed().is_ok_and(|age| age <= ttl))
+    }
+}
+
+/**
+    The cache key of estimating `program` under `config`: a SHA-256 over length-prefixed
+    fields, so no two different inputs can run together into the same bytes. The versions
+    of qcore and of the qsharp compiler and estimator are fields too, so results cached on
+    disk are not served after either changes.
+**/
+pub fn key(program: &Program, config: &EstimationConfig) -> String {
+    let mut hasher = Sha256::new();
+    field(&mut hasher, env!("CARGO_PKG_VERSION"));
+    field(&mut hasher, env!("QSHARP_VERSION"));
+
+    let sources = &program.package_graph_sources;
+    package(&mut hasher, &sources.root);
+    let mut packages: Vec<_> = sources.packages.iter().collect();
+    packages.sort_by_key(|(name, _)| *name);
+    field(&mut hasher, &packages.len().to_string());
+    for (name, info) in packages {
+        field(&mut hasher, name);
+        package(&mut hasher, info);
+    }
+
+    field(&mut hasher, program.entry.as_deref().unwrap_or_default());
+    field(&mut hasher, config.profile.name());
+    field(&mut hasher, &serde_json::to_string(&config.params).expect("params serialize to JSON"));
+
+    format!("{:x}", hasher.finalize())
+}
+
+fn package(hasher: &mut Sha256, info: &PackageInfo) {
+    let mut sources: Vec<_> = info.sources.iter().collect();
+    sources.sort_by(|(a, _), (b, _)| a.cmp(b));
+    field(hasher, &sources.len().to_string());
+    for (name, contents) in sources {
+        field(hasher, name);
+        field(hasher, &contents.replace("\r\n", "\n"));
+    }
+
+    field(hasher, &info.language_features.len().to_string());
+    for feature in &info.language_features {
+        field(hasher, feature);
+    }
+
+    let mut dependencies: Vec<_> = info.dependencies.iter().collect();
+    dependencies.sort();
+    field(hasher, &dependencies.len().to_string());
+    for (alias, key) in dependencies {
+        field(hasher, alias);
+        field(hasher, key);
+    }
+}
+
+fn env_number(name: &str, expected: &str) -> Result<Option<u64>> {
+    let Ok(value) = std::env::var(name) else {
+        return Ok(None);
+    };
+    value.trim().parse().map(Some).map_err(|_| Error::Config {
+        label: name.to_string(),
+        message: format!("expected {expected}, got `{value}`"),
+    })
+}
+
+fn field(hasher: &mut Sha256, value: &str) {
+    hasher.update((value.len() as u64).to_le_bytes());
+    hasher.update(value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+    use super::{key, Memory, ResultCache};
+    use crate::{default_estimation_config, Program, QubitParams};
+
+    #[test]
+    fn evicts_the_least_recently_used_results() {
+        let cache = ResultCache::in_memory(None).with_capacity(2);
+        let mut memory = Memory::default();
+        cache.remember(&mut memory, "a", SystemTime::now(), "{}");
+        cache.remember(&mut memory, "b", SystemTime::now(), "{}");
+        memory.clock += 1;
+        memory.entries.get_mut("a").unwrap().used = memory.clock;
+        cache.remember(&mut memory, "c", SystemTime::now(), "{}");
+
+        let mut keys: Vec<&str> = memory.entries.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["a", "c"]);
+    }
+
+    #[test]
+    fn keeps_nothing_in_memory_without_capacity() {
+        let cache = ResultCache::in_memory(None).with_capacity(0);
+        let mut memory = Memory::default();
+        cache.remember(&mut memory, "a", SystemTime::now(), "{}");
+        assert!(memory.entries.is_empty());
+    }
+
+    #[test]
+    fn keys_ignore_labels_and_line_endings_but_not_params() {
+        let program = |source: &str| Program::from_source("a.qs".into(), source.into());
+        let config = default_estimation_config();
+        let unix = key(&program("operation Main() : Unit {\n}\n"), &config);
+
+        let relabeled = crate::EstimationConfig {
+            label: "other".to_string(),
+            ..config.clone()
+        };
+        assert_eq!(key(&program("operation Main() : Unit {\r\n}\r\n"), &config), unix);
+        assert_eq!(key(&program("operation Main() : Unit {\n}\n"), &relabeled), unix);
+
+        let mut other_qubit = config.clone();
+        other_qubit.params.qubit_params = QubitParams::preset("qubit_gate_ns_e3");
+        assert_ne!(key(&program("operation Main() : Unit {\n}\n"), &other_qubit), unix);
+    }
+}
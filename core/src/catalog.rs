@@ -0,0 +1,266 @@
+use std::sync::OnceLock;
+use miette::Result;
+use resource_estimator::estimate_physical_resources_from_json;
+use serde::Serialize;
+
+use crate::config::{InstructionSet, Params, QEC_SCHEME_PRESETS};
+use crate::formula;
+use crate::output::{split_items, LogicalCounts, QecScheme, QubitParams};
+
+/**
+    Every qubit model and QEC scheme the estimator has built in, with the parameters a
+    preset name stands for.
+**/
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Catalog {
+    pub qubit_params: Vec<QubitPreset>,
+    pub qec_schemes: Vec<QecSchemePreset>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QubitPreset {
+    pub description: &'static str,
+    pub params: QubitParams,
+}
+
+/**
+    A QEC scheme preset for one instruction set; `surface_code` has different parameters
+    for gate-based and Majorana qubits, `floquet_code` only exists for Majorana qubits.
+**/
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QecSchemePreset {
+    pub description: &'static str,
+    pub instruction_set: InstructionSet,
+    pub params: QecScheme,
+}
+
+impl Catalog {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("the catalog serializes to JSON")
+    }
+}
+
+impl QubitPreset {
+    pub fn instruction_set(&self) -> InstructionSet {
+        instruction_set(&self.params)
+    }
+}
+
+/// The estimator's qubit presets, with what they model. Their parameters are read back
+/// from the estimator rather than copied here, so they cannot drift from it.
+const QUBIT_PRESETS: &[(&str, &str)] = &[
+    ("qubit_gate_ns_e3", "Gate-based qubit with ns operation times and 1e-3 error rates, e.g. superconducting transmons"),
+    ("qubit_gate_ns_e4", "Gate-based qubit with ns operation times and 1e-4 error rates, e.g. superconducting transmons"),
+    ("qubit_gate_us_e3", "Gate-based qubit with µs operation times and 1e-3 error rates, e.g. trapped ions"),
+    ("qubit_gate_us_e4", "Gate-based qubit with µs operation times and 1e-4 error rates, e.g. trapped ions"),
+    ("qubit_maj_ns_e4", "Majorana qubit with ns operation times and 1e-4 error rates"),
+    ("qubit_maj_ns_e6", "Majorana qubit with ns operation times and 1e-6 error rates"),
+];
+
+/// What each QEC preset is on the qubits it supports; which qubits those are is up to the estimator.
+const QEC_SCHEME_DESCRIPTIONS: &[(&str, InstructionSet, &str)] = &[
+    ("surface_code", InstructionSet::GateBased, "Surface code on gate-based qubits"),
+    ("surface_code", InstructionSet::Majorana, "Surface code on Majorana qubits, built from joint measurements"),
+    ("floquet_code", InstructionSet::Majorana, "Floquet code, only available on Majorana qubits"),
+];
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/**
+    The presets as the estimator resolves them, asked once per process: each qubit model
+    under the surface code, and each QEC scheme with the first qubit model of every
+    instruction set, keeping the combinations the estimator accepts.
+**/
+pub fn catalog() -> crate::Result<Catalog> {
+    if let Some(catalog) = CATALOG.get() {
+        return Ok(catalog.clone());
+    }
+
+    let mut qubit_params = Vec::new();
+    for &(name, description) in QUBIT_PRESETS {
+        let (params, _) = resolve(name, "surface_code").map_err(|message| preset_error(name, "surface_code", message))?;
+        qubit_params.push(QubitPreset { description, params });
+    }
+
+    let mut qec_schemes = Vec::new();
+    for &scheme in QEC_SCHEME_PRESETS {
+        for instruction_set in [InstructionSet::GateBased, InstructionSet::Majorana] {
+            let Some(qubit) = qubit_params.iter().find(|preset| preset.instruction_set() == instruction_set) else {
+                continue;
+            };
+            // The estimator rejects schemes the qubit's instruction set cannot run.
+            let Ok((_, params)) = resolve(&qubit.params.name, scheme) else {
+                continue;
+            };
+            let description = QEC_SCHEME_DESCRIPTIONS
+                .iter()
+                .find(|(name, set, _)| *name == scheme && *set == instruction_set)
+                .map_or("", |(_, _, description)| description);
+            qec_schemes.push(QecSchemePreset {
+                description,
+                instruction_set,
+                params,
+            });
+        }
+    }
+
+    Ok(CATALOG
+        .get_or_init(|| Catalog {
+            qubit_params,
+            qec_schemes,
+        })
+        .clone())
+}
+
+/**
+    Checks that the qubit model and QEC scheme fit together before anything is compiled:
+    a QEC preset is available for the qubit's instruction set, and custom QEC formulas
+    only use operation times that instruction set has. Qubit presets missing from the
+    catalog are looked up in the estimator, which rejects names it does not know.
+**/
+pub fn validate_compatibility(params: &Params) -> Result<()> {
+    let catalog = catalog().map_err(miette::Error::new)?;
+    let qubit = &params.qubit_params;
+    let instruction_set = match qubit.instruction_set {
+        Some(instruction_set) => instruction_set,
+        None => match catalog.qubit_params.iter().find(|preset| preset.params.name == qubit.name) {
+            Some(preset) => preset.instruction_set(),
+            None => {
+                let (resolved, _) = resolve(&qubit.name, "surface_code").map_err(|message| {
+                    miette::Error::msg(format!("qubit params `{}`: {message}", qubit.name))
+                })?;
+                instruction_set(&resolved)
+            }
+        },
+    };
+
+    let scheme = &params.qec_scheme;
+    let matching: Vec<&QecSchemePreset> =
+        catalog.qec_schemes.iter().filter(|preset| preset.params.name == scheme.name).collect();
+    if !matching.is_empty() && !matching.iter().any(|preset| preset.instruction_set == instruction_set) {
+        let supported: Vec<String> = matching.iter().map(|preset| format!("{:?}", preset.instruction_set)).collect();
+        return Err(miette::Error::msg(format!(
+            "QEC scheme `{}` requires {} qubits, but qubit params `{}` are {:?}",
+            scheme.name,
+            supported.join(" or "),
+            qubit.name,
+            instruction_set
+        )));
+    }
+
+    let available = operation_times(instruction_set);
+    for (field, formula) in [
+        ("logicalCycleTime", &scheme.logical_cycle_time),
+        ("physicalQubitsPerLogicalQubit", &scheme.physical_qubits_per_logical_qubit),
+    ] {
+        let Some(formula) = formula else { continue };
+        if let Some(variable) = formula::variables(formula)
+            .into_iter()
+            .find(|variable| variable.ends_with("Time") && !available.contains(&variable.as_str()))
+        {
+            return Err(miette::Error::msg(format!(
+                "QEC scheme `{}`: {field} uses {variable}, which {:?} qubits do not have (available: {})",
+                scheme.name,
+                instruction_set,
+                available.join(", ")
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/**
+    The qubit and QEC parameters the estimator uses for the named presets, from the job
+    params it echoes for a trivial algorithm, or the estimator's error for the pair.
+**/
+fn resolve(qubit: &str, scheme: &str) -> std::result::Result<(QubitParams, QecScheme), String> {
+    let counts = LogicalCounts {
+        num_qubits: 1,
+        t_count: 1,
+        measurement_count: 1,
+        ..LogicalCounts::default()
+    };
+    let job_params = serde_json::json!([{ "qubitParams": { "name": qubit }, "qecScheme": { "name": scheme } }]);
+    let counts = serde_json::to_string(&counts).expect("logical counts serialize to JSON");
+
+    let result = estimate_physical_resources_from_json(&counts, &job_params.to_string()).map_err(|e| e.to_string())?;
+    let item = split_items(&result)
+        .map_err(|e| e.to_string())?
+        .pop()
+        .ok_or("the estimator returned no result")??;
+    let job_params = &item["jobParams"];
+    Ok((
+        serde_json::from_value(job_params["qubitParams"].clone()).map_err(|e| e.to_string())?,
+        serde_json::from_value(job_params["qecScheme"].clone()).map_err(|e| e.to_string())?,
+    ))
+}
+
+fn preset_error(qubit: &str, scheme: &str, message: String) -> crate::Error {
+    crate::Error::Estimation {
+        label: format!("{qubit} + {scheme}"),
+        message: format!("could not resolve the preset: {message}"),
+        constraints: None,
+    }
+}
+
+fn instruction_set(params: &QubitParams) -> InstructionSet {
+    match params.instruction_set.as_str() {
+        "Majorana" => InstructionSet::Majorana,
+        _ => InstructionSet::GateBased,
+    }
+}
+
+fn operation_times(instruction_set: InstructionSet) -> &'static [&'static str] {
+    match instruction_set {
+        InstructionSet::GateBased => &["oneQubitMeasurementTime", "oneQubitGateTime", "twoQubitGateTime", "tGateTime"],
+        InstructionSet::Majorana => &["oneQubitMeasurementTime", "twoQubitJointMeasurementTime", "tGateTime"],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{catalog, validate_compatibility, QUBIT_PRESETS, QEC_SCHEME_DESCRIPTIONS};
+    use crate::config::{InstructionSet, Params, QecScheme, QubitParams};
+
+    #[test]
+    fn every_listed_preset_resolves_in_the_estimator() {
+        let catalog = catalog().expect("the estimator resolves every listed preset");
+        assert_eq!(catalog.qubit_params.len(), QUBIT_PRESETS.len());
+        for preset in &catalog.qubit_params {
+            let majorana = preset.params.name.starts_with("qubit_maj");
+            assert_eq!(preset.instruction_set() == InstructionSet::Majorana, majorana, "{}", preset.params.name);
+        }
+
+        // Every described scheme is one the estimator accepts, and no accepted one lacks a description.
+        let schemes: Vec<(&str, InstructionSet)> =
+            catalog.qec_schemes.iter().map(|preset| (preset.params.name.as_str(), preset.instruction_set)).collect();
+        let described: Vec<(&str, InstructionSet)> =
+            QEC_SCHEME_DESCRIPTIONS.iter().map(|&(name, set, _)| (name, set)).collect();
+        assert_eq!(schemes, described);
+    }
+
+    #[test]
+    fn resolves_the_parameters_of_a_preset() {
+        let catalog = catalog().unwrap();
+        let preset = catalog.qubit_params.iter().find(|preset| preset.params.name == "qubit_gate_ns_e3").unwrap();
+        assert_eq!(preset.params.t_gate_time, "50 ns");
+        assert_eq!(preset.params.t_gate_error_rate, 1e-3);
+    }
+
+    #[test]
+    fn rejects_floquet_code_on_gate_based_qubits() {
+        let params = Params {
+            qubit_params: QubitParams::preset("qubit_gate_ns_e3"),
+            qec_scheme: QecScheme::preset("floquet_code"),
+            error_budget: None,
+            constraints: None,
+            distillation_unit_specifications: None,
+        };
+        let error = validate_compatibility(&params).unwrap_err().to_string();
+        assert!(error.contains("requires Majorana qubits"), "{error}");
+    }
+}
@@ -0,0 +1,975 @@
+use miette::{Context, Result};
+use qsc::target::Profile;
+use serde::{Deserialize, Serialize};
+
+use crate::{catalog, formula};
+pub use crate::output::MeasurementErrorRate;
+
+/**
+    One configuration to estimate under. Read from a TOML (`.toml`) or JSON file, it can
+    name a hardware profile of the profile registry instead of giving params:
+
+    ```toml
+    label = "2027 roadmap"
+    hardware = "ourlab-2027"
+    ```
+
+    `Registry::resolve` replaces the params with the profile's; a config still naming a
+    hardware profile is rejected when estimated.
+**/
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EstimationConfig {
+    pub label: String,
+    #[serde(default)]
+    pub detail: String,
+    #[serde(default)]
+    pub profile: TargetProfile,
+    /// Hardware profile of the profile registry whose params to estimate with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware: Option<String>,
+    #[serde(default = "default_params")]
+    pub params: Params,
+}
+
+impl EstimationConfig {
+    pub fn load(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        crate::load_toml_or_json(path, |message| crate::Error::Config {
+            label: path.display().to_string(),
+            message,
+        })
+    }
+}
+
+/**
+    Target profile the program is compiled for, i.e. how much classical computation on
+    measurement results the hardware supports. Programs using constructs outside the
+    profile are rejected when they are compiled.
+**/
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TargetProfile {
+    #[serde(rename = "base")]
+    Base,
+    #[default]
+    #[serde(rename = "adaptive_ri")]
+    AdaptiveRI,
+    #[serde(rename = "unrestricted")]
+    Unrestricted,
+}
+
+impl TargetProfile {
+    pub const ALL: [TargetProfile; 3] = [TargetProfile::Base, TargetProfile::AdaptiveRI, TargetProfile::Unrestricted];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TargetProfile::Base => "base",
+            TargetProfile::AdaptiveRI => "adaptive_ri",
+            TargetProfile::Unrestricted => "unrestricted",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            TargetProfile::Base => "no branching on measurement results; measurements only at the end",
+            TargetProfile::AdaptiveRI => {
+                "branching on measurement results, qubit reuse and integer computations, but no floating-point computations on results"
+            }
+            TargetProfile::Unrestricted => "any Q# program",
+        }
+    }
+}
+
+impl std::fmt::Display for TargetProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for TargetProfile {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|profile| profile.name()).collect();
+                format!("unknown target profile `{name}`, expected one of {}", names.join(", "))
+            })
+    }
+}
+
+impl From<TargetProfile> for Profile {
+    fn from(profile: TargetProfile) -> Self {
+        match profile {
+            TargetProfile::Base => Profile::Base,
+            TargetProfile::AdaptiveRI => Profile::AdaptiveRI,
+            TargetProfile::Unrestricted => Profile::Unrestricted,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Params {
+    #[serde(rename = "qubitParams")]
+    pub qubit_params: QubitParams,
+    #[serde(rename = "qecScheme")]
+    pub qec_scheme: QecScheme,
+    #[serde(rename = "errorBudget", skip_serializing_if = "Option::is_none")]
+    pub error_budget: Option<ErrorBudget>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraints: Option<Constraints>,
+    /// Replaces the estimator's built-in distillation units when set.
+    #[serde(rename = "distillationUnitSpecifications", skip_serializing_if = "Option::is_none")]
+    pub distillation_unit_specifications: Option<Vec<DistillationUnitSpecification>>,
+}
+
+/**
+    Total probability of failure allowed for the algorithm. Either a single total,
+    which the estimator splits between logical errors, T-state distillation and
+    rotation synthesis, or that split given explicitly. Without one the estimator
+    uses a total of 1e-3.
+**/
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ErrorBudget {
+    Total(f64),
+    #[serde(rename_all = "camelCase")]
+    Partitioned {
+        logical: f64,
+        t_states: f64,
+        rotations: f64,
+    },
+}
+
+/**
+    Physical qubit model. With only `name` set this selects one of the estimator's
+    presets (e.g. `qubit_maj_ns_e6`); any other field overrides the preset's value.
+    Setting `instruction_set` describes a fully custom model, which then has to
+    provide every time and error rate its instruction set needs.
+**/
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct QubitParams {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_set: Option<InstructionSet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_qubit_measurement_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_qubit_gate_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub two_qubit_gate_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub two_qubit_joint_measurement_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub t_gate_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_qubit_measurement_error_rate: Option<MeasurementErrorRate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_qubit_gate_error_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub two_qubit_gate_error_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub two_qubit_joint_measurement_error_rate: Option<MeasurementErrorRate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub t_gate_error_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_error_rate: Option<f64>,
+}
+
+/// A field name paired with whether the config sets it.
+type FieldPresence<'a> = (&'a str, bool);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstructionSet {
+    GateBased,
+    Majorana,
+}
+
+/**
+    Quantum error correction scheme. With only `name` set this selects one of the
+    estimator's presets (`surface_code`, `floquet_code`); any other field overrides
+    the preset's value. A scheme with any other name is custom and has to define
+    the threshold, crossing prefactor and both formulas.
+
+    Formulas may use `codeDistance` and the qubit's operation times, e.g.
+    `"20 * oneQubitMeasurementTime * codeDistance"`.
+**/
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct QecScheme {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_correction_threshold: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crossing_prefactor: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logical_cycle_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub physical_qubits_per_logical_qubit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_code_distance: Option<u32>,
+}
+
+pub const QEC_SCHEME_PRESETS: &[&str] = &["surface_code", "floquet_code"];
+
+const QEC_FORMULA_VARIABLES: &[&str] = &[
+    "codeDistance",
+    "oneQubitGateTime",
+    "twoQubitGateTime",
+    "oneQubitMeasurementTime",
+    "twoQubitJointMeasurementTime",
+    "tGateTime",
+];
+
+impl QubitParams {
+    pub fn preset(name: impl Into<String>) -> Self {
+        QubitParams {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Checks a custom qubit model before it reaches the estimator, whose own
+    /// errors for missing fields do not say which instruction set needed them.
+    pub fn validate(&self) -> Result<()> {
+        let rates = [
+            ("oneQubitMeasurementErrorRate.process", self.one_qubit_measurement_error_rate.map(|r| r.process)),
+            ("oneQubitMeasurementErrorRate.readout", self.one_qubit_measurement_error_rate.map(|r| r.readout)),
+            ("oneQubitGateErrorRate", self.one_qubit_gate_error_rate),
+            ("twoQubitGateErrorRate", self.two_qubit_gate_error_rate),
+            ("twoQubitJointMeasurementErrorRate.process", self.two_qubit_joint_measurement_error_rate.map(|r| r.process)),
+            ("twoQubitJointMeasurementErrorRate.readout", self.two_qubit_joint_measurement_error_rate.map(|r| r.readout)),
+            ("tGateErrorRate", self.t_gate_error_rate),
+            ("idleErrorRate", self.idle_error_rate),
+        ];
+        for (field, rate) in rates {
+            if let Some(rate) = rate {
+                if !(0.0..=1.0).contains(&rate) {
+                    return Err(miette::Error::msg(format!(
+                        "qubit params `{}`: {field} must be between 0 and 1, got {rate}",
+                        self.name
+                    )));
+                }
+            }
+        }
+
+        let Some(instruction_set) = self.instruction_set else {
+            return Ok(());
+        };
+
+        let (required, unsupported): (&[FieldPresence], &[FieldPresence]) = match instruction_set {
+            InstructionSet::GateBased => (
+                &[
+                    ("oneQubitMeasurementTime", self.one_qubit_measurement_time.is_some()),
+                    ("oneQubitGateTime", self.one_qubit_gate_time.is_some()),
+                    ("twoQubitGateTime", self.two_qubit_gate_time.is_some()),
+                    ("oneQubitMeasurementErrorRate", self.one_qubit_measurement_error_rate.is_some()),
+                    ("oneQubitGateErrorRate", self.one_qubit_gate_error_rate.is_some()),
+                    ("twoQubitGateErrorRate", self.two_qubit_gate_error_rate.is_some()),
+                    ("tGateErrorRate", self.t_gate_error_rate.is_some()),
+                ],
+                &[
+                    ("twoQubitJointMeasurementTime", self.two_qubit_joint_measurement_time.is_some()),
+                    ("twoQubitJointMeasurementErrorRate", self.two_qubit_joint_measurement_error_rate.is_some()),
+                ],
+            ),
+            InstructionSet::Majorana => (
+                &[
+                    ("oneQubitMeasurementTime", self.one_qubit_measurement_time.is_some()),
+                    ("oneQubitMeasurementErrorRate", self.one_qubit_measurement_error_rate.is_some()),
+                    ("tGateErrorRate", self.t_gate_error_rate.is_some()),
+                ],
+                &[
+                    ("oneQubitGateTime", self.one_qubit_gate_time.is_some()),
+                    ("twoQubitGateTime", self.two_qubit_gate_time.is_some()),
+                    ("oneQubitGateErrorRate", self.one_qubit_gate_error_rate.is_some()),
+                    ("twoQubitGateErrorRate", self.two_qubit_gate_error_rate.is_some()),
+                ],
+            ),
+        };
+
+        let missing: Vec<&str> = required.iter().filter(|(_, set)| !set).map(|(field, _)| *field).collect();
+        if !missing.is_empty() {
+            return Err(miette::Error::msg(format!(
+                "qubit params `{}`: {:?} instruction set requires {}",
+                self.name,
+                instruction_set,
+                missing.join(", ")
+            )));
+        }
+
+        let extra: Vec<&str> = unsupported.iter().filter(|(_, set)| *set).map(|(field, _)| *field).collect();
+        if !extra.is_empty() {
+            return Err(miette::Error::msg(format!(
+                "qubit params `{}`: {:?} instruction set does not use {}",
+                self.name,
+                instruction_set,
+                extra.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl QecScheme {
+    pub fn preset(name: impl Into<String>) -> Self {
+        QecScheme {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !QEC_SCHEME_PRESETS.contains(&self.name.as_str()) {
+            let missing: Vec<&str> = [
+                ("errorCorrectionThreshold", self.error_correction_threshold.is_some()),
+                ("crossingPrefactor", self.crossing_prefactor.is_some()),
+                ("logicalCycleTime", self.logical_cycle_time.is_some()),
+                ("physicalQubitsPerLogicalQubit", self.physical_qubits_per_logical_qubit.is_some()),
+            ]
+            .iter()
+            .filter(|(_, set)| !set)
+            .map(|(field, _)| *field)
+            .collect();
+            if !missing.is_empty() {
+                return Err(miette::Error::msg(format!(
+                    "QEC scheme `{}` is not a preset ({}), so it requires {}",
+                    self.name,
+                    QEC_SCHEME_PRESETS.join(", "),
+                    missing.join(", ")
+                )));
+            }
+        }
+
+        if let Some(threshold) = self.error_correction_threshold {
+            if !(threshold > 0.0 && threshold < 1.0) {
+                return Err(miette::Error::msg(format!(
+                    "QEC scheme `{}`: errorCorrectionThreshold must be between 0 and 1, got {threshold}",
+                    self.name
+                )));
+            }
+        }
+        if let Some(prefactor) = self.crossing_prefactor {
+            if prefactor <= 0.0 {
+                return Err(miette::Error::msg(format!(
+                    "QEC scheme `{}`: crossingPrefactor must be positive, got {prefactor}",
+                    self.name
+                )));
+            }
+        }
+        if self.max_code_distance == Some(0) {
+            return Err(miette::Error::msg(format!(
+                "QEC scheme `{}`: maxCodeDistance must be a positive integer",
+                self.name
+            )));
+        }
+
+        for (field, formula) in [
+            ("logicalCycleTime", &self.logical_cycle_time),
+            ("physicalQubitsPerLogicalQubit", &self.physical_qubits_per_logical_qubit),
+        ] {
+            if let Some(formula) = formula {
+                formula::validate(formula, QEC_FORMULA_VARIABLES)
+                    .with_context(|| format!("QEC scheme `{}`: {field}", self.name))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/**
+    Limits the estimator has to respect when choosing code distances and T factories.
+    `max_duration` and `max_physical_qubits` are mutually exclusive: with one set, the
+    estimator minimizes the other, e.g. the fastest run that fits in 1M qubits.
+**/
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Constraints {
+    /// Stretches the algorithm's logical depth so fewer T factories keep up, must be at least 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logical_depth_factor: Option<f64>,
+    #[serde(rename = "maxTFactories", skip_serializing_if = "Option::is_none")]
+    pub max_t_factories: Option<u32>,
+    /// Time with unit, e.g. `"1 s"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_physical_qubits: Option<u64>,
+}
+
+/// The units a time may have, with their length in nanoseconds.
+const TIME_UNITS: &[(&str, f64)] = &[("ns", 1.0), ("µs", 1e3), ("us", 1e3), ("ms", 1e6), ("s", 1e9)];
+
+/**
+    Nanoseconds in a time with unit, e.g. 1000 for `"1 µs"`.
+**/
+pub(crate) fn nanoseconds(time: &str) -> Option<f64> {
+    let (value, unit) = time.split_once(' ')?;
+    let (_, scale) = TIME_UNITS.iter().find(|(name, _)| *name == unit)?;
+    Some(value.parse::<f64>().ok()? * scale)
+}
+
+impl Constraints {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_duration.is_some() && self.max_physical_qubits.is_some() {
+            return Err(miette::Error::msg(
+                "constraints: maxDuration and maxPhysicalQubits cannot both be set, \
+                 the estimator minimizes one subject to the other",
+            ));
+        }
+        if let Some(factor) = self.logical_depth_factor {
+            if factor < 1.0 {
+                return Err(miette::Error::msg(format!(
+                    "constraints: logicalDepthFactor must be at least 1, got {factor}"
+                )));
+            }
+        }
+        if self.max_physical_qubits == Some(0) {
+            return Err(miette::Error::msg("constraints: maxPhysicalQubits must be positive"));
+        }
+        if let Some(duration) = &self.max_duration {
+            if !nanoseconds(duration).is_some_and(|nanoseconds| nanoseconds > 0.0) {
+                let units: Vec<&str> = TIME_UNITS.iter().map(|(unit, _)| *unit).collect();
+                return Err(miette::Error::msg(format!(
+                    "constraints: maxDuration must be a positive time with unit ({}), e.g. \"1 s\", got \"{duration}\"",
+                    units.join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Constraints {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(factor) = self.logical_depth_factor {
+            parts.push(format!("logicalDepthFactor = {factor}"));
+        }
+        if let Some(max) = self.max_t_factories {
+            parts.push(format!("maxTFactories = {max}"));
+        }
+        if let Some(max) = &self.max_duration {
+            parts.push(format!("maxDuration = {max}"));
+        }
+        if let Some(max) = self.max_physical_qubits {
+            parts.push(format!("maxPhysicalQubits = {max}"));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/**
+    A magic state distillation protocol for T factories. Either just the `name` of a
+    built-in unit (`15-1 RM`, `15-1 space-efficient`) or a custom protocol turning
+    `num_input_ts` T states into `num_output_ts` better ones.
+
+    Formulas may use `inputErrorRate` (`z`), `cliffordErrorRate` (`c`) and
+    `readoutErrorRate` (`r`), e.g. `"35.0 * inputErrorRate ^ 3 + 7.1 * cliffordErrorRate"`.
+    A custom unit needs at least one of the physical and logical qubit
+    specifications, which say how it runs directly on physical qubits or on
+    error corrected logical qubits in a round.
+**/
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DistillationUnitSpecification {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_input_ts: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_output_ts: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_probability_formula: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_error_rate_formula: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub physical_qubit_specification: Option<DistillationUnitQubitSpecification>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logical_qubit_specification: Option<DistillationUnitQubitSpecification>,
+    /// Used instead of `logical_qubit_specification` when the unit runs in the first round.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logical_qubit_specification_first_round_override: Option<DistillationUnitQubitSpecification>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DistillationUnitQubitSpecification {
+    pub num_unit_qubits: u64,
+    pub duration_in_qubit_cycle_time: u64,
+}
+
+pub const DISTILLATION_UNIT_PRESETS: &[&str] = &["15-1 RM", "15-1 space-efficient"];
+
+const DISTILLATION_FORMULA_VARIABLES: &[&str] = &[
+    "inputErrorRate",
+    "cliffordErrorRate",
+    "readoutErrorRate",
+    "z",
+    "c",
+    "r",
+];
+
+impl DistillationUnitSpecification {
+    pub fn preset(name: impl Into<String>) -> Self {
+        DistillationUnitSpecification {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    fn label(&self) -> &str {
+        self.display_name
+            .as_deref()
+            .or(self.name.as_deref())
+            .unwrap_or("<unnamed>")
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(name) = &self.name {
+            if DISTILLATION_UNIT_PRESETS.contains(&name.as_str()) {
+                if *self != Self::preset(name.as_str()) {
+                    return Err(miette::Error::msg(format!(
+                        "distillation unit `{name}` is built in and cannot be combined with custom fields"
+                    )));
+                }
+                return Ok(());
+            }
+        }
+
+        let missing: Vec<&str> = [
+            ("numInputTs", self.num_input_ts.is_some()),
+            ("numOutputTs", self.num_output_ts.is_some()),
+            ("failureProbabilityFormula", self.failure_probability_formula.is_some()),
+            ("outputErrorRateFormula", self.output_error_rate_formula.is_some()),
+        ]
+        .iter()
+        .filter(|(_, set)| !set)
+        .map(|(field, _)| *field)
+        .collect();
+        if !missing.is_empty() {
+            return Err(miette::Error::msg(format!(
+                "distillation unit `{}` is not built in ({}), so it requires {}",
+                self.label(),
+                DISTILLATION_UNIT_PRESETS.join(", "),
+                missing.join(", ")
+            )));
+        }
+
+        if self.num_input_ts == Some(0) || self.num_output_ts == Some(0) {
+            return Err(miette::Error::msg(format!(
+                "distillation unit `{}`: numInputTs and numOutputTs must be positive",
+                self.label()
+            )));
+        }
+
+        if self.physical_qubit_specification.is_none() && self.logical_qubit_specification.is_none() {
+            return Err(miette::Error::msg(format!(
+                "distillation unit `{}` requires physicalQubitSpecification, logicalQubitSpecification or both",
+                self.label()
+            )));
+        }
+        if self.logical_qubit_specification_first_round_override.is_some() && self.logical_qubit_specification.is_none() {
+            return Err(miette::Error::msg(format!(
+                "distillation unit `{}`: logicalQubitSpecificationFirstRoundOverride requires logicalQubitSpecification",
+                self.label()
+            )));
+        }
+        for (field, specification) in [
+            ("physicalQubitSpecification", self.physical_qubit_specification),
+            ("logicalQubitSpecification", self.logical_qubit_specification),
+            ("logicalQubitSpecificationFirstRoundOverride", self.logical_qubit_specification_first_round_override),
+        ] {
+            if let Some(specification) = specification {
+                if specification.num_unit_qubits == 0 || specification.duration_in_qubit_cycle_time == 0 {
+                    return Err(miette::Error::msg(format!(
+                        "distillation unit `{}`: {field} needs a positive numUnitQubits and durationInQubitCycleTime",
+                        self.label()
+                    )));
+                }
+            }
+        }
+
+        for (field, formula) in [
+            ("failureProbabilityFormula", &self.failure_probability_formula),
+            ("outputErrorRateFormula", &self.output_error_rate_formula),
+        ] {
+            if let Some(formula) = formula {
+                formula::validate(formula, DISTILLATION_FORMULA_VARIABLES)
+                    .with_context(|| format!("distillation unit `{}`: {field}", self.label()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ErrorBudget {
+    pub fn total(&self) -> f64 {
+        match *self {
+            ErrorBudget::Total(total) => total,
+            ErrorBudget::Partitioned { logical, t_states, rotations } => logical + t_states + rotations,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let ErrorBudget::Partitioned { logical, t_states, rotations } = *self {
+            if logical <= 0.0 {
+                return Err(miette::Error::msg(format!(
+                    "error budget: logical part must be positive, got {logical}"
+                )));
+            }
+            if t_states < 0.0 || rotations < 0.0 {
+                return Err(miette::Error::msg(format!(
+                    "error budget: T-state and rotation parts must not be negative, got {t_states} and {rotations}"
+                )));
+            }
+        }
+
+        let total = self.total();
+        if !(total > 0.0 && total < 1.0) {
+            return Err(miette::Error::msg(format!(
+                "error budget must be between 0 and 1 (exclusive), got a total of {total}"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl Params {
+    pub fn validate(&self) -> Result<()> {
+        self.qubit_params.validate()?;
+        self.qec_scheme.validate()?;
+        if let Some(error_budget) = &self.error_budget {
+            error_budget.validate()?;
+        }
+        if let Some(constraints) = &self.constraints {
+            constraints.validate()?;
+        }
+        if let Some(units) = &self.distillation_unit_specifications {
+            if units.is_empty() {
+                return Err(miette::Error::msg(
+                    "distillationUnitSpecifications must list at least one unit, or be left out for the built-in ones",
+                ));
+            }
+            for unit in units {
+                unit.validate()?;
+            }
+        }
+        catalog::validate_compatibility(self)?;
+        Ok(())
+    }
+}
+
+pub fn default_estimation_config() -> EstimationConfig {
+    EstimationConfig {
+        label: "qubit_maj_ns_e6 + surface_code".to_string(),
+        detail: "Majorana qubit with 1e-6 error rate (surface code QEC)".to_string(),
+        profile: TargetProfile::default(),
+        hardware: None,
+        params: default_params(),
+    }
+}
+
+fn default_params() -> Params {
+    Params {
+        qubit_params: QubitParams::preset("qubit_maj_ns_e6"),
+        qec_scheme: QecScheme::preset("surface_code"),
+        error_budget: None,
+        constraints: None,
+        distillation_unit_specifications: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        nanoseconds, Constraints, DistillationUnitQubitSpecification, DistillationUnitSpecification, ErrorBudget,
+        InstructionSet, MeasurementErrorRate, QubitParams,
+    };
+
+    fn gate_based() -> QubitParams {
+        let rate = MeasurementErrorRate { process: 1e-4, readout: 1e-4 };
+        QubitParams {
+            name: "lab_gate".to_string(),
+            instruction_set: Some(InstructionSet::GateBased),
+            one_qubit_measurement_time: Some("100 ns".to_string()),
+            one_qubit_gate_time: Some("50 ns".to_string()),
+            two_qubit_gate_time: Some("50 ns".to_string()),
+            t_gate_time: Some("50 ns".to_string()),
+            one_qubit_measurement_error_rate: Some(rate),
+            one_qubit_gate_error_rate: Some(1e-4),
+            two_qubit_gate_error_rate: Some(1e-4),
+            t_gate_error_rate: Some(1e-4),
+            ..QubitParams::default()
+        }
+    }
+
+    fn majorana() -> QubitParams {
+        QubitParams {
+            name: "lab_majorana".to_string(),
+            instruction_set: Some(InstructionSet::Majorana),
+            one_qubit_measurement_time: Some("100 ns".to_string()),
+            two_qubit_joint_measurement_time: Some("100 ns".to_string()),
+            t_gate_time: Some("100 ns".to_string()),
+            one_qubit_measurement_error_rate: Some(MeasurementErrorRate { process: 1e-4, readout: 1e-4 }),
+            t_gate_error_rate: Some(0.05),
+            ..QubitParams::default()
+        }
+    }
+
+    fn error(params: &QubitParams) -> String {
+        params.validate().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn accepts_complete_custom_models_and_presets() {
+        gate_based().validate().unwrap();
+        majorana().validate().unwrap();
+        QubitParams::preset("qubit_gate_ns_e3").validate().unwrap();
+    }
+
+    #[test]
+    fn lists_the_fields_an_instruction_set_requires() {
+        let params = QubitParams {
+            one_qubit_gate_time: None,
+            t_gate_error_rate: None,
+            ..gate_based()
+        };
+        assert_eq!(
+            error(&params),
+            "qubit params `lab_gate`: GateBased instruction set requires oneQubitGateTime, tGateErrorRate"
+        );
+
+        let params = QubitParams {
+            one_qubit_measurement_error_rate: None,
+            ..majorana()
+        };
+        assert!(error(&params).ends_with("Majorana instruction set requires oneQubitMeasurementErrorRate"));
+    }
+
+    #[test]
+    fn rejects_fields_an_instruction_set_does_not_use() {
+        let params = QubitParams {
+            two_qubit_joint_measurement_time: Some("100 ns".to_string()),
+            ..gate_based()
+        };
+        assert_eq!(
+            error(&params),
+            "qubit params `lab_gate`: GateBased instruction set does not use twoQubitJointMeasurementTime"
+        );
+
+        let params = QubitParams {
+            one_qubit_gate_time: Some("50 ns".to_string()),
+            two_qubit_gate_error_rate: Some(1e-4),
+            ..majorana()
+        };
+        assert!(error(&params).ends_with("Majorana instruction set does not use oneQubitGateTime, twoQubitGateErrorRate"));
+    }
+
+    #[test]
+    fn rejects_error_rates_outside_zero_to_one() {
+        let params = QubitParams {
+            idle_error_rate: Some(1.5),
+            ..QubitParams::preset("qubit_gate_ns_e3")
+        };
+        assert_eq!(error(&params), "qubit params `qubit_gate_ns_e3`: idleErrorRate must be between 0 and 1, got 1.5");
+
+        let params = QubitParams {
+            one_qubit_measurement_error_rate: Some(MeasurementErrorRate { process: 1e-4, readout: -1e-4 }),
+            ..gate_based()
+        };
+        assert!(error(&params).contains("oneQubitMeasurementErrorRate.readout must be between 0 and 1"));
+    }
+
+    fn partitioned(logical: f64, t_states: f64, rotations: f64) -> ErrorBudget {
+        ErrorBudget::Partitioned { logical, t_states, rotations }
+    }
+
+    #[test]
+    fn sums_partitioned_error_budgets() {
+        assert_eq!(ErrorBudget::Total(1e-3).total(), 1e-3);
+        assert_eq!(partitioned(0.25, 0.125, 0.0625).total(), 0.4375);
+        partitioned(1e-4, 0.0, 0.0).validate().unwrap();
+        ErrorBudget::Total(0.5).validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_error_budgets_outside_zero_to_one() {
+        for budget in [ErrorBudget::Total(0.0), ErrorBudget::Total(-1e-3), ErrorBudget::Total(1.0), ErrorBudget::Total(2.0)] {
+            assert!(budget.validate().unwrap_err().to_string().starts_with("error budget must be between 0 and 1"));
+        }
+        // Each part is valid, but together they exceed the total probability of 1.
+        assert_eq!(
+            partitioned(0.5, 0.3, 0.2).validate().unwrap_err().to_string(),
+            "error budget must be between 0 and 1 (exclusive), got a total of 1"
+        );
+    }
+
+    #[test]
+    fn rejects_non_positive_error_budget_parts() {
+        assert_eq!(
+            partitioned(0.0, 1e-4, 1e-4).validate().unwrap_err().to_string(),
+            "error budget: logical part must be positive, got 0"
+        );
+        assert_eq!(
+            partitioned(1e-4, -1e-4, 0.0).validate().unwrap_err().to_string(),
+            "error budget: T-state and rotation parts must not be negative, got -0.0001 and 0"
+        );
+    }
+
+    #[test]
+    fn reads_times_with_units() {
+        assert_eq!(nanoseconds("250 ns"), Some(250.0));
+        assert_eq!(nanoseconds("1.5 µs"), Some(1500.0));
+        assert_eq!(nanoseconds("1.5 us"), Some(1500.0));
+        assert_eq!(nanoseconds("2 ms"), Some(2e6));
+        assert_eq!(nanoseconds("1 s"), Some(1e9));
+        for time in ["1s", "1 min", "one s", "1  s", ""] {
+            assert_eq!(nanoseconds(time), None, "{time}");
+        }
+    }
+
+    #[test]
+    fn accepts_positive_max_durations() {
+        for duration in ["1 s", "0.5 ms", "10 us"] {
+            let constraints = Constraints {
+                max_duration: Some(duration.to_string()),
+                ..Constraints::default()
+            };
+            constraints.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_max_durations() {
+        for duration in ["0 s", "-1 s", "1s", "1 min"] {
+            let constraints = Constraints {
+                max_duration: Some(duration.to_string()),
+                ..Constraints::default()
+            };
+            let error = constraints.validate().unwrap_err().to_string();
+            assert!(error.starts_with("constraints: maxDuration must be a positive time with unit (ns, µs, us, ms, s)"), "{error}");
+        }
+    }
+
+    #[test]
+    fn rejects_both_max_duration_and_max_physical_qubits() {
+        let constraints = Constraints {
+            max_duration: Some("1 s".to_string()),
+            max_physical_qubits: Some(1_000_000),
+            ..Constraints::default()
+        };
+        assert!(constraints
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .starts_with("constraints: maxDuration and maxPhysicalQubits cannot both be set"));
+
+        let constraints = Constraints {
+            logical_depth_factor: Some(0.5),
+            ..Constraints::default()
+        };
+        assert_eq!(
+            constraints.validate().unwrap_err().to_string(),
+            "constraints: logicalDepthFactor must be at least 1, got 0.5"
+        );
+    }
+
+    fn custom_unit() -> DistillationUnitSpecification {
+        DistillationUnitSpecification {
+            display_name: Some("lab 15-1".to_string()),
+            num_input_ts: Some(15),
+            num_output_ts: Some(1),
+            failure_probability_formula: Some("15.0 * inputErrorRate + 356.0 * cliffordErrorRate".to_string()),
+            output_error_rate_formula: Some("35.0 * inputErrorRate ^ 3 + 7.1 * cliffordErrorRate".to_string()),
+            physical_qubit_specification: Some(DistillationUnitQubitSpecification {
+                num_unit_qubits: 31,
+                duration_in_qubit_cycle_time: 24,
+            }),
+            ..DistillationUnitSpecification::default()
+        }
+    }
+
+    fn unit_error(unit: &DistillationUnitSpecification) -> String {
+        unit.validate().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn accepts_built_in_and_complete_custom_units() {
+        DistillationUnitSpecification::preset("15-1 RM").validate().unwrap();
+        DistillationUnitSpecification::preset("15-1 space-efficient").validate().unwrap();
+        custom_unit().validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_built_in_units_with_custom_fields() {
+        let unit = DistillationUnitSpecification {
+            num_input_ts: Some(20),
+            ..DistillationUnitSpecification::preset("15-1 RM")
+        };
+        assert_eq!(
+            unit_error(&unit),
+            "distillation unit `15-1 RM` is built in and cannot be combined with custom fields"
+        );
+    }
+
+    #[test]
+    fn lists_the_fields_a_custom_unit_requires() {
+        let unit = DistillationUnitSpecification {
+            num_output_ts: None,
+            output_error_rate_formula: None,
+            ..custom_unit()
+        };
+        assert_eq!(
+            unit_error(&unit),
+            "distillation unit `lab 15-1` is not built in (15-1 RM, 15-1 space-efficient), so it requires numOutputTs, outputErrorRateFormula"
+        );
+
+        let unit = DistillationUnitSpecification {
+            physical_qubit_specification: None,
+            ..custom_unit()
+        };
+        assert!(unit_error(&unit).ends_with("requires physicalQubitSpecification, logicalQubitSpecification or both"));
+
+        let unit = DistillationUnitSpecification {
+            logical_qubit_specification_first_round_override: custom_unit().physical_qubit_specification,
+            ..custom_unit()
+        };
+        assert!(unit_error(&unit).ends_with("logicalQubitSpecificationFirstRoundOverride requires logicalQubitSpecification"));
+    }
+
+    #[test]
+    fn rejects_empty_units_and_unknown_formula_variables() {
+        let unit = DistillationUnitSpecification {
+            num_input_ts: Some(0),
+            ..custom_unit()
+        };
+        assert_eq!(unit_error(&unit), "distillation unit `lab 15-1`: numInputTs and numOutputTs must be positive");
+
+        let unit = DistillationUnitSpecification {
+            physical_qubit_specification: Some(DistillationUnitQubitSpecification {
+                num_unit_qubits: 0,
+                duration_in_qubit_cycle_time: 24,
+            }),
+            ..custom_unit()
+        };
+        assert!(unit_error(&unit).ends_with("physicalQubitSpecification needs a positive numUnitQubits and durationInQubitCycleTime"));
+
+        let unit = DistillationUnitSpecification {
+            output_error_rate_formula: Some("35.0 * codeDistance".to_string()),
+            ..custom_unit()
+        };
+        let report = unit.validate().unwrap_err();
+        let messages: Vec<String> = report.chain().map(ToString::to_string).collect();
+        assert_eq!(messages[0], "distillation unit `lab 15-1`: outputErrorRateFormula");
+        assert!(messages[1].contains("unknown variable `codeDistance`"), "{messages:?}");
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use miette::Result;
+
+use crate::EstimationOutput;
+
+/// A count compared between two estimates, by name and how to read it.
+type Metric = (&'static str, fn(&EstimationOutput) -> u64);
+
+/**
+    Every count a diff compares. All of them are costs, so any increase is a candidate
+    regression.
+**/
+pub const METRICS: &[Metric] = &[
+    ("physicalQubits", |output| output.physical_counts.physical_qubits),
+    ("runtime", |output| output.physical_counts.runtime),
+    ("rqops", |output| output.physical_counts.rqops),
+    ("codeDistance", |output| output.logical_qubit.code_distance),
+    ("algorithmicLogicalQubits", |output| output.physical_counts.breakdown.algorithmic_logical_qubits),
+    ("algorithmicLogicalDepth", |output| output.physical_counts.breakdown.algorithmic_logical_depth),
+    ("logicalDepth", |output| output.physical_counts.breakdown.logical_depth),
+    ("numTstates", |output| output.physical_counts.breakdown.num_tstates),
+    ("numTfactories", |output| output.physical_counts.breakdown.num_tfactories),
+    ("numTfactoryRuns", |output| output.physical_counts.breakdown.num_tfactory_runs),
+    ("physicalQubitsForAlgorithm", |output| output.physical_counts.breakdown.physical_qubits_for_algorithm),
+    ("physicalQubitsForTfactories", |output| output.physical_counts.breakdown.physical_qubits_for_tfactories),
+    ("numQubits", |output| output.logical_counts.num_qubits),
+    ("tCount", |output| output.logical_counts.t_count),
+    ("rotationCount", |output| output.logical_counts.rotation_count),
+    ("rotationDepth", |output| output.logical_counts.rotation_depth),
+    ("cczCount", |output| output.logical_counts.ccz_count),
+    ("ccixCount", |output| output.logical_counts.ccix_count),
+    ("measurementCount", |output| output.logical_counts.measurement_count),
+];
+
+/**
+    How much relative increase of each count is tolerated before it is flagged as a
+    regression, e.g. 0.05 for 5%. Counts without their own threshold use `default`.
+**/
+#[derive(Clone, Debug, Default)]
+pub struct Thresholds {
+    pub default: f64,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+impl Thresholds {
+    pub fn uniform(threshold: f64) -> Self {
+        Thresholds {
+            default: threshold,
+            metrics: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, metric: &str) -> f64 {
+        self.metrics.get(metric).copied().unwrap_or(self.default)
+    }
+
+    /// Checks that every threshold names a metric, so a typo does not silently leave a
+    /// count at the default.
+    pub fn validate(&self) -> Result<()> {
+        for (metric, threshold) in &self.metrics {
+            if !METRICS.iter().any(|(name, _)| name == metric) {
+                let names: Vec<&str> = METRICS.iter().map(|(name, _)| *name).collect();
+                return Err(miette::Error::msg(format!(
+                    "unknown metric `{metric}`, expected one of {}",
+                    names.join(", ")
+                )));
+            }
+            if *threshold < 0.0 {
+                return Err(miette::Error::msg(format!(
+                    "the threshold of `{metric}` must not be negative, got {threshold}"
+                )));
+            }
+        }
+        if self.default < 0.0 {
+            return Err(miette::Error::msg(format!(
+                "the threshold must not be negative, got {}",
+                self.default
+            )));
+        }
+        Ok(())
+    }
+}
+
+/**
+    Two estimates side by side, e.g. before and after a code change.
+**/
+pub struct EstimationDiff {
+    pub before: EstimationOutput,
+    pub after: EstimationOutput,
+    pub deltas: Vec<Delta>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Delta {
+    pub metric: &'static str,
+    pub before: u64,
+    pub after: u64,
+    pub absolute: i128,
+    /// Change relative to `before`; `None` when `before` is 0.
+    pub relative: Option<f64>,
+    /// Whether the count grew by more than its threshold (from nothing, by anything).
+    pub regression: bool,
+}
+
+impl EstimationDiff {
+    pub fn new(before: EstimationOutput, after: EstimationOutput, thresholds: &Thresholds) -> Self {
+        let deltas = METRICS
+            .iter()
+            .map(|(metric, count)| {
+                let (old, new) = (count(&before), count(&after));
+                let relative = (old != 0).then(|| (new as f64 - old as f64) / old as f64);
+                Delta {
+                    metric,
+                    before: old,
+                    after: new,
+                    absolute: i128::from(new) - i128::from(old),
+                    relative,
+                    regression: match relative {
+                        Some(relative) => relative > thresholds.get(metric),
+                        None => new > old,
+                    },
+                }
+            })
+            .collect();
+
+        EstimationDiff { before, after, deltas }
+    }
+
+    pub fn regressions(&self) -> impl Iterator<Item = &Delta> {
+        self.deltas.iter().filter(|delta| delta.regression)
+    }
+
+    pub fn has_regressions(&self) -> bool {
+        self.regressions().next().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Delta, EstimationDiff, Thresholds, METRICS};
+    use crate::output;
+
+    fn delta<'a>(diff: &'a EstimationDiff, metric: &str) -> &'a Delta {
+        diff.deltas.iter().find(|delta| delta.metric == metric).unwrap()
+    }
+
+    #[test]
+    fn accepts_thresholds_for_known_metrics() {
+        let mut thresholds = Thresholds::uniform(0.05);
+        thresholds.metrics.insert("runtime".to_string(), 0.2);
+        assert!(thresholds.validate().is_ok());
+        assert_eq!(thresholds.get("runtime"), 0.2);
+        assert_eq!(thresholds.get("physicalQubits"), 0.05);
+    }
+
+    #[test]
+    fn rejects_unknown_metrics_and_negative_thresholds() {
+        let mut thresholds = Thresholds::default();
+        thresholds.metrics.insert("qubits".to_string(), 0.1);
+        let error = thresholds.validate().unwrap_err().to_string();
+        assert!(error.contains("unknown metric `qubits`") && error.contains("physicalQubits"), "{error}");
+
+        let mut thresholds = Thresholds::default();
+        thresholds.metrics.insert("runtime".to_string(), -0.1);
+        assert!(thresholds.validate().unwrap_err().to_string().contains("`runtime` must not be negative"));
+
+        assert!(Thresholds::uniform(-0.01).validate().is_err());
+    }
+
+    #[test]
+    fn compares_every_metric() {
+        let diff = EstimationDiff::new(output::sample(), output::sample(), &Thresholds::default());
+        assert_eq!(diff.deltas.len(), METRICS.len());
+        assert!(diff.deltas.iter().all(|delta| delta.absolute == 0 && !delta.regression));
+        assert!(!diff.has_regressions());
+    }
+
+    #[test]
+    fn signs_the_change_by_direction() {
+        let mut after = output::sample();
+        after.physical_counts.physical_qubits = 15000;
+        after.physical_counts.runtime = 300000;
+        let diff = EstimationDiff::new(output::sample(), after, &Thresholds::default());
+
+        let qubits = delta(&diff, "physicalQubits");
+        assert_eq!((qubits.before, qubits.after, qubits.absolute), (12000, 15000, 3000));
+        assert_eq!(qubits.relative, Some(0.25));
+        assert!(qubits.regression);
+
+        let runtime = delta(&diff, "runtime");
+        assert_eq!(runtime.absolute, -100000);
+        assert_eq!(runtime.relative, Some(-0.25));
+        assert!(!runtime.regression);
+    }
+
+    #[test]
+    fn only_flags_increases_above_the_threshold() {
+        let mut after = output::sample();
+        after.physical_counts.physical_qubits = 12600;
+        after.physical_counts.runtime = 440000;
+
+        let mut thresholds = Thresholds::uniform(0.05);
+        thresholds.metrics.insert("runtime".to_string(), 0.2);
+        let diff = EstimationDiff::new(output::sample(), after, &thresholds);
+
+        // 5% more qubits is at the threshold, 10% more runtime below its own one.
+        assert!(!diff.has_regressions());
+
+        let mut after = output::sample();
+        after.physical_counts.physical_qubits = 12601;
+        let diff = EstimationDiff::new(output::sample(), after, &thresholds);
+        let regressions: Vec<&str> = diff.regressions().map(|delta| delta.metric).collect();
+        assert_eq!(regressions, ["physicalQubits"]);
+    }
+
+    #[test]
+    fn flags_counts_that_appear_from_nothing() {
+        let mut after = output::sample();
+        after.logical_counts.rotation_count = 8;
+        let diff = EstimationDiff::new(output::sample(), after, &Thresholds::uniform(10.0));
+
+        let rotations = delta(&diff, "rotationCount");
+        assert_eq!(rotations.relative, None);
+        assert!(rotations.regression);
+        assert!(!delta(&diff, "cczCount").regression);
+    }
+}
@@ -0,0 +1,220 @@
+use miette::Diagnostic;
+use qsc::interpret;
+use thiserror::Error;
+
+use crate::TargetProfile;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/**
+    Everything that can go wrong between reading a program and parsing the estimator's
+    answer. Compiler and interpreter failures keep every diagnostic the compiler produced,
+    with its source, so miette can render them with spans.
+**/
+#[derive(Debug, Error, Diagnostic)]
+pub enum Error {
+    #[error("could not read `{path}`")]
+    #[diagnostic(code(qcore::read))]
+    Read {
+        path: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error("could not load Q# project `{path}`")]
+    #[diagnostic(code(qcore::project))]
+    Project {
+        path: String,
+        #[related]
+        errors: Vec<qsc_project::Error>,
+    },
+
+    #[error("could not compile `{program}` for the `{profile}` target profile")]
+    #[diagnostic(code(qcore::compile))]
+    Compile {
+        program: String,
+        profile: TargetProfile,
+        #[related]
+        errors: Vec<interpret::Error>,
+        #[help]
+        help: Option<String>,
+    },
+
+    #[error("could not trace the logical counts of `{program}`")]
+    #[diagnostic(code(qcore::interpreter))]
+    Interpreter {
+        program: String,
+        #[related]
+        errors: Vec<interpret::Error>,
+    },
+
+    #[error("{label}: {message}{}", under_constraints(.constraints))]
+    #[diagnostic(code(qcore::estimation))]
+    Estimation {
+        label: String,
+        message: String,
+        constraints: Option<String>,
+    },
+
+    #[error("invalid configuration `{label}`: {message}")]
+    #[diagnostic(code(qcore::config))]
+    Config { label: String, message: String },
+
+    #[error("invalid profile registry `{path}`: {message}")]
+    #[diagnostic(code(qcore::registry))]
+    Registry { path: String, message: String },
+
+    #[error("could not use result cache `{path}`")]
+    #[diagnostic(code(qcore::cache))]
+    Cache {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("`git {command}` failed in `{repo}`: {message}")]
+    #[diagnostic(code(qcore::git))]
+    Git {
+        repo: String,
+        command: String,
+        message: String,
+    },
+
+    #[error("invalid budget annotation: {message}")]
+    #[diagnostic(code(qcore::annotation))]
+    Annotation {
+        message: String,
+        #[source_code]
+        source_code: miette::NamedSource<String>,
+        #[label]
+        span: miette::SourceSpan,
+    },
+
+    #[error("invalid logical counts: {0}")]
+    #[diagnostic(code(qcore::counts))]
+    Counts(String),
+
+    #[error("could not parse resource estimator output: {0}")]
+    #[diagnostic(code(qcore::output))]
+    Output(String),
+}
+
+/**
+    One compiler diagnostic, flattened for consumers that cannot render miette reports,
+    e.g. GraphQL error extensions.
+**/
+#[derive(Debug, Clone)]
+pub struct ErrorDiagnostic {
+    pub message: String,
+    pub code: Option<String>,
+    pub help: Option<String>,
+    pub locations: Vec<SourceLocation>,
+}
+
+/**
+    A labeled span of a diagnostic; lines and columns start at 1.
+**/
+#[derive(Debug, Clone)]
+pub struct SourceLocation {
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub label: Option<String>,
+}
+
+impl Error {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Read { .. } => "read",
+            Error::Project { .. } => "project",
+            Error::Compile { .. } => "compile",
+            Error::Interpreter { .. } => "interpreter",
+            Error::Estimation { .. } => "estimation",
+            Error::Config { .. } => "config",
+            Error::Registry { .. } => "registry",
+            Error::Cache { .. } => "cache",
+            Error::Git { .. } => "git",
+            Error::Annotation { .. } => "annotation",
+            Error::Counts(_) => "counts",
+            Error::Output(_) => "output",
+        }
+    }
+
+    /// The diagnostics behind this error: every related compiler diagnostic, or the error
+    /// itself when it has none.
+    pub fn diagnostics(&self) -> Vec<ErrorDiagnostic> {
+        match self.related() {
+            Some(related) => related.map(describe).collect(),
+            None => vec![describe(self)],
+        }
+    }
+
+    pub(crate) fn read(path: impl std::fmt::Display, source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Error::Read {
+            path: path.to_string(),
+            source: source.into(),
+        }
+    }
+
+    /// Keeps the whole context chain of a validation error, e.g.
+    /// "qubit params `custom`: oneQubitGateTime is required".
+    pub(crate) fn config(label: &str, report: &miette::Report) -> Self {
+        Error::Config {
+            label: label.to_string(),
+            message: messages(report),
+        }
+    }
+
+    pub(crate) fn cache(path: &std::path::Path, source: std::io::Error) -> Self {
+        Error::Cache {
+            path: path.display().to_string(),
+            source,
+        }
+    }
+
+    pub(crate) fn registry(path: &std::path::Path, message: impl std::fmt::Display) -> Self {
+        Error::Registry {
+            path: path.display().to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/**
+    Joins a report's whole context chain into one message.
+**/
+pub(crate) fn messages(report: &miette::Report) -> String {
+    let messages: Vec<String> = report.chain().map(ToString::to_string).collect();
+    messages.join(": ")
+}
+
+fn under_constraints(constraints: &Option<String>) -> String {
+    constraints
+        .as_ref()
+        .map(|constraints| format!(" (estimated under constraints: {constraints})"))
+        .unwrap_or_default()
+}
+
+fn describe(diagnostic: &dyn Diagnostic) -> ErrorDiagnostic {
+    let locations = match (diagnostic.source_code(), diagnostic.labels()) {
+        (Some(source), Some(labels)) => labels
+            .filter_map(|label| {
+                let contents = source.read_span(label.inner(), 0, 0).ok()?;
+                Some(SourceLocation {
+                    file: contents.name().map(str::to_string),
+                    line: contents.line() + 1,
+                    column: contents.column() + 1,
+                    label: label.label().map(str::to_string),
+                })
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    ErrorDiagnostic {
+        message: diagnostic.to_string(),
+        code: diagnostic.code().map(|code| code.to_string()),
+        help: diagnostic.help().map(|help| help.to_string()),
+        locations,
+    }
+}
@@ -0,0 +1,222 @@
+use serde_json::Value;
+
+use crate::{Error, EstimationOutput, FrontierOutput, Result};
+
+/**
+    How to render a result: the estimator's whole output as JSON or YAML, or summarized as
+    CSV, a Markdown table or an aligned terminal table of the formatted physical counts.
+**/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+    Csv,
+    Markdown,
+    #[default]
+    Table,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 5] = [
+        OutputFormat::Json,
+        OutputFormat::Yaml,
+        OutputFormat::Csv,
+        OutputFormat::Markdown,
+        OutputFormat::Table,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Table => "table",
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|format| format.name()).collect();
+                format!("unknown output format `{name}`, expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/**
+    Renders an estimate; the summaries list the formatted physical counts. JSON and YAML
+    add `cacheHit: true` to the estimator's output when the estimate came from a cache.
+**/
+pub fn render_estimate(output: &EstimationOutput, format: OutputFormat) -> Result<String> {
+    let raw = || {
+        let mut value = parse(output.raw_json())?;
+        if let (true, Value::Object(fields)) = (output.cache_hit, &mut value) {
+            fields.insert("cacheHit".to_string(), Value::Bool(true));
+        }
+        Ok(value)
+    };
+    Ok(match format {
+        OutputFormat::Json => json(raw()?),
+        OutputFormat::Yaml => yaml(raw()?),
+        OutputFormat::Csv => output.to_csv(),
+        OutputFormat::Markdown => markdown(&["Resource", "Estimate"], &summary(output)),
+        OutputFormat::Table => table(&["Resource", "Estimate"], &summary(output)),
+    })
+}
+
+/**
+    Renders a frontier; the summaries have one row per Pareto-optimal point, while JSON and
+    YAML keep every point the estimator returned.
+**/
+pub fn render_frontier(frontier: &FrontierOutput, format: OutputFormat) -> Result<String> {
+    let header = ["Runtime", "Physical qubits", "Code distance", "T factories"];
+    let rows: Vec<Vec<String>> = frontier
+        .frontier_entries
+        .iter()
+        .map(|entry| {
+            let counts = &entry.physical_counts_formatted;
+            vec![
+                counts.runtime.clone(),
+                counts.physical_qubits.clone(),
+                entry.logical_qubit.code_distance.to_string(),
+                counts.num_tfactories.clone(),
+            ]
+        })
+        .collect();
+
+    Ok(match format {
+        OutputFormat::Json => json(parse(frontier.raw_json())?),
+        OutputFormat::Yaml => yaml(parse(frontier.raw_json())?),
+        OutputFormat::Csv => frontier.to_csv(),
+        OutputFormat::Markdown => markdown(&header, &rows),
+        OutputFormat::Table => table(&header, &rows),
+    })
+}
+
+/// The formatted physical counts worth reading first, as label and value rows.
+fn summary(output: &EstimationOutput) -> Vec<Vec<String>> {
+    let counts = &output.physical_counts_formatted;
+    [
+        ("Physical qubits", &counts.physical_qubits),
+        ("Runtime", &counts.runtime),
+        ("rQOPS", &counts.rqops),
+        ("Logical qubits (algorithm)", &counts.algorithmic_logical_qubits),
+        ("Logical depth", &counts.logical_depth),
+        ("T states", &counts.num_tstates),
+        ("T factories", &counts.num_tfactories),
+        ("T factory runs", &counts.num_tfactory_runs),
+        ("Physical qubits (algorithm)", &counts.physical_qubits_for_algorithm),
+        ("Physical qubits (T factories)", &counts.physical_qubits_for_tfactories),
+        ("Physical qubits per logical qubit", &counts.physical_qubits_per_logical_qubit),
+        ("Logical cycle time", &counts.logical_cycle_time),
+        ("Logical error rate", &counts.logical_error_rate),
+        ("Required logical qubit error rate", &counts.required_logical_qubit_error_rate),
+    ]
+    .into_iter()
+    .map(|(label, value)| vec![label.to_string(), value.clone()])
+    .collect()
+}
+
+/// The estimator's output, pretty-printed from its JSON so fields qcore does not model are kept.
+fn json(value: Value) -> String {
+    let mut json = serde_json::to_string_pretty(&value).expect("JSON values serialize to JSON");
+    json.push('\n');
+    json
+}
+
+fn yaml(value: Value) -> String {
+    serde_norway::to_string(&value).expect("JSON values serialize to YAML")
+}
+
+/// The estimator's JSON of a result. Results deserialized rather than read from the
+/// estimator have none, so there is nothing to render as it is.
+fn parse(raw: &str) -> Result<Value> {
+    serde_json::from_str(raw).map_err(|e| Error::Output(format!("the estimator's output is not available as JSON: {e}")))
+}
+
+fn markdown(header: &[&str], rows: &[Vec<String>]) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut markdown = line(header.iter().map(ToString::to_string).collect());
+    markdown.push_str(&line(header.iter().map(|_| "---".to_string()).collect()));
+    for row in rows {
+        markdown.push_str(&line(row.iter().map(|cell| cell.replace('|', "\\|")).collect()));
+    }
+    markdown
+}
+
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].chars().count()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let line = |cells: Vec<&str>| {
+        let cells: Vec<String> = cells
+            .into_iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        format!("{}\n", cells.join("  ").trim_end())
+    };
+
+    let mut table = line(header.to_vec());
+    let rules: Vec<String> = widths.iter().map(|width| "─".repeat(*width)).collect();
+    table.push_str(&line(rules.iter().map(String::as_str).collect()));
+    for row in rows {
+        table.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json, parse, render_estimate, yaml, OutputFormat};
+    use crate::{output, Error, EstimationOutput};
+
+    const RAW: &str = r#"{"status":"success","extra":{"note":"a: b","items":[1,2.5,null]},"empty":[]}"#;
+
+    #[test]
+    fn keeps_every_field_in_order() {
+        assert_eq!(
+            json(parse(RAW).unwrap()),
+            "{\n  \"status\": \"success\",\n  \"extra\": {\n    \"note\": \"a: b\",\n    \"items\": [\n      1,\n      2.5,\n      null\n    ]\n  },\n  \"empty\": []\n}\n"
+        );
+    }
+
+    #[test]
+    fn writes_yaml_that_reads_back_as_the_same_value() {
+        let yaml = yaml(parse(RAW).unwrap());
+        assert!(yaml.starts_with("status: success\n"));
+        let value: serde_json::Value = serde_norway::from_str(&yaml).unwrap();
+        assert_eq!(value, serde_json::from_str::<serde_json::Value>(RAW).unwrap());
+    }
+
+    #[test]
+    fn reports_results_without_the_estimators_json() {
+        let mut output: EstimationOutput = serde_json::from_str(output::sample().raw_json()).unwrap();
+        assert!(matches!(render_estimate(&output, OutputFormat::Json), Err(Error::Output(_))));
+        assert!(render_estimate(&output, OutputFormat::Table).is_ok());
+
+        output = output::sample();
+        output.cache_hit = true;
+        let yaml = render_estimate(&output, OutputFormat::Yaml).unwrap();
+        assert!(yaml.contains("cacheHit: true"), "{yaml}");
+    }
+}
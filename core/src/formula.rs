@@ -0,0 +1,280 @@
+//! Syntax check for the formula strings the estimator evaluates at run time, e.g.
+//! `"20 * oneQubitMeasurementTime * codeDistance"`. The estimator only reports a
+//! bad formula once it gets to evaluating it, so configs are checked up front.
+//!
+//! Grammar: numbers, the given variables, `+ - * / ^`, parentheses, unary minus
+//! and calls to the functions in `FUNCTIONS`.
+
+use miette::Result;
+
+const FUNCTIONS: &[&str] = &["abs", "ceil", "floor", "round", "log", "min", "max"];
+
+pub fn validate(formula: &str, variables: &[&str]) -> Result<()> {
+    let tokens = tokenize(formula)?;
+    let mut parser = Parser {
+        formula,
+        tokens: &tokens,
+        position: 0,
+        variables,
+    };
+    parser.expression()?;
+    match parser.peek() {
+        None => Ok(()),
+        Some((offset, token)) => Err(parser.error(offset, &format!("unexpected `{token}`"))),
+    }
+}
+
+/**
+    The variables a formula refers to; empty if it does not even tokenize, which
+    `validate` reports.
+**/
+pub fn variables(formula: &str) -> Vec<String> {
+    let Ok(tokens) = tokenize(formula) else {
+        return Vec::new();
+    };
+    tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, token))| match (token, tokens.get(index + 1)) {
+            (Token::Identifier(_), Some((_, Token::Open))) => None,
+            (Token::Identifier(name), _) => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number,
+    Identifier(String),
+    Operator(char),
+    Open,
+    Close,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number => write!(f, "number"),
+            Token::Identifier(name) => write!(f, "{name}"),
+            Token::Operator(op) => write!(f, "{op}"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+fn tokenize(formula: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut chars = formula.char_indices().peekable();
+
+    while let Some(&(offset, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_ascii_digit() || c == '.' {
+                    chars.next();
+                } else if c == 'e' || c == 'E' {
+                    chars.next();
+                    if let Some(&(_, '+' | '-')) = chars.peek() {
+                        chars.next();
+                    }
+                } else {
+                    break;
+                }
+            }
+            let end = chars.peek().map_or(formula.len(), |&(end, _)| end);
+            if formula[offset..end].parse::<f64>().is_err() {
+                return Err(formula_error(formula, offset, &format!("invalid number `{}`", &formula[offset..end])));
+            }
+            tokens.push((offset, Token::Number));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    name.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push((offset, Token::Identifier(name)));
+        } else {
+            let token = match c {
+                '+' | '-' | '*' | '/' | '^' => Token::Operator(c),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                ',' => Token::Comma,
+                _ => return Err(formula_error(formula, offset, &format!("unexpected character `{c}`"))),
+            };
+            tokens.push((offset, token));
+            chars.next();
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    formula: &'a str,
+    tokens: &'a [(usize, Token)],
+    position: usize,
+    variables: &'a [&'a str],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<(usize, &Token)> {
+        self.tokens.get(self.position).map(|(offset, token)| (*offset, token))
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat_operator(&mut self, operators: &[char]) -> bool {
+        match self.peek() {
+            Some((_, Token::Operator(op))) if operators.contains(op) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expression(&mut self) -> Result<()> {
+        self.term()?;
+        while self.eat_operator(&['+', '-']) {
+            self.term()?;
+        }
+        Ok(())
+    }
+
+    fn term(&mut self) -> Result<()> {
+        self.power()?;
+        while self.eat_operator(&['*', '/']) {
+            self.power()?;
+        }
+        Ok(())
+    }
+
+    fn power(&mut self) -> Result<()> {
+        self.unary()?;
+        if self.eat_operator(&['^']) {
+            self.power()?;
+        }
+        Ok(())
+    }
+
+    fn unary(&mut self) -> Result<()> {
+        if self.eat_operator(&['-', '+']) {
+            return self.unary();
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<()> {
+        match self.next() {
+            Some((_, Token::Number)) => Ok(()),
+            Some((offset, Token::Identifier(name))) => {
+                if let Some((_, Token::Open)) = self.peek() {
+                    if !FUNCTIONS.contains(&name.as_str()) {
+                        return Err(self.error(offset, &format!("unknown function `{name}`")));
+                    }
+                    self.position += 1;
+                    self.expression()?;
+                    while let Some((_, Token::Comma)) = self.peek() {
+                        self.position += 1;
+                        self.expression()?;
+                    }
+                    self.close(offset)
+                } else if self.variables.contains(&name.as_str()) {
+                    Ok(())
+                } else {
+                    Err(self.error(
+                        offset,
+                        &format!("unknown variable `{name}`, expected one of {}", self.variables.join(", ")),
+                    ))
+                }
+            }
+            Some((offset, Token::Open)) => {
+                self.expression()?;
+                self.close(offset)
+            }
+            Some((offset, token)) => Err(self.error(offset, &format!("unexpected `{token}`"))),
+            None => Err(self.error(self.formula.len(), "unexpected end of formula")),
+        }
+    }
+
+    fn close(&mut self, open: usize) -> Result<()> {
+        match self.next() {
+            Some((_, Token::Close)) => Ok(()),
+            _ => Err(self.error(open, "unclosed `(`")),
+        }
+    }
+
+    fn error(&self, offset: usize, message: &str) -> miette::Error {
+        formula_error(self.formula, offset, message)
+    }
+}
+
+fn formula_error(formula: &str, offset: usize, message: &str) -> miette::Error {
+    miette::Error::msg(format!("invalid formula `{formula}` at position {offset}: {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, variables};
+
+    const VARIABLES: &[&str] = &["codeDistance", "oneQubitMeasurementTime", "physicalErrorRate"];
+
+    fn error(formula: &str) -> String {
+        validate(formula, VARIABLES).expect_err(formula).to_string()
+    }
+
+    #[test]
+    fn accepts_valid_formulas() {
+        for formula in [
+            "20 * oneQubitMeasurementTime * codeDistance",
+            "2 * codeDistance^2",
+            "-codeDistance + 1.5e-3",
+            "0.03 * (physicalErrorRate / 0.01)^((codeDistance + 1) / 2)",
+            "max(1, ceil(log(codeDistance)))",
+            "2^-codeDistance",
+        ] {
+            assert!(validate(formula, VARIABLES).is_ok(), "{formula}");
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_variables_and_functions() {
+        assert!(error("2 * distance").contains("unknown variable `distance`"));
+        assert!(error("sqrt(codeDistance)").contains("unknown function `sqrt`"));
+    }
+
+    #[test]
+    fn rejects_unbalanced_parentheses() {
+        assert!(error("(codeDistance + 1").contains("unclosed `(`"));
+        assert!(error("max(1, codeDistance").contains("unclosed `(`"));
+        assert!(error("codeDistance + 1)").contains("unexpected `)`"));
+    }
+
+    #[test]
+    fn rejects_malformed_numbers_and_expressions() {
+        assert!(error("1.2.3 * codeDistance").contains("invalid number `1.2.3`"));
+        assert!(error("2e * codeDistance").contains("invalid number `2e`"));
+        assert!(error("codeDistance *").contains("unexpected end of formula"));
+        assert!(error("codeDistance % 2").contains("unexpected character `%`"));
+        assert!(error("").contains("unexpected end of formula"));
+    }
+
+    #[test]
+    fn lists_variables_but_not_functions() {
+        assert_eq!(variables("max(codeDistance, 2) * physicalErrorRate"), ["codeDistance", "physicalErrorRate"]);
+        assert!(variables("2 $ codeDistance").is_empty());
+    }
+}
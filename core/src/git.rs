@@ -0,0 +1,357 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use qsc::ast::{Item, StmtKind, TopLevelNode};
+
+use crate::program::MANIFEST_FILE;
+use crate::{
+    estimate_program, estimate_program_diff, EstimationConfig, EstimationDiff, EstimationOutput, Error, Program, Result,
+    Thresholds,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileChange {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/**
+    What a Q# program changed between two revisions costs: its estimate at the revision it
+    exists in, or the diff of both.
+**/
+pub enum FileEstimate {
+    Added(EstimationOutput),
+    Deleted(EstimationOutput),
+    Modified(Box<EstimationDiff>),
+}
+
+pub struct FileDiff {
+    /// Path relative to the repository root: of the changed file, or of the `qsharp.json`
+    /// manifest of the project it belongs to.
+    pub path: String,
+    pub change: FileChange,
+    pub estimate: Result<FileEstimate>,
+}
+
+/**
+    Estimates every Q# program changed between the revisions `base` and `head` of the git
+    repository at `repo`, under the same configuration, reading both versions from the
+    repository's object database rather than the working tree. A changed file inside a
+    project (the nearest directory above it with a `qsharp.json` manifest) stands for the
+    whole project, estimated once however many of its files changed; other files are
+    programs of their own. Programs without an `@EntryPoint()` are libraries with nothing
+    to estimate: a version without one counts as absent, and programs without one at
+    either revision are left out.
+**/
+pub fn estimate_revisions(
+    repo: impl AsRef<Path>,
+    base: &str,
+    head: &str,
+    config: &EstimationConfig,
+    thresholds: &Thresholds,
+) -> Result<Vec<FileDiff>> {
+    let repo = repo.as_ref();
+    let (mut before, mut after) = (Tree::new(repo, base)?, Tree::new(repo, head)?);
+
+    let mut units = Vec::new();
+    for (_, path) in changed_files(repo, &before.commit, &after.commit)? {
+        let unit = match after.manifest_of(&path).or_else(|| before.manifest_of(&path)) {
+            Some(manifest) => Unit::Project(manifest),
+            None => Unit::File(path),
+        };
+        if !units.contains(&unit) {
+            units.push(unit);
+        }
+    }
+
+    let mut files = Vec::new();
+    for unit in units {
+        let estimate = match (before.program(&unit), after.program(&unit)) {
+            (Ok(None), Ok(None)) => continue,
+            (Ok(Some(before)), Ok(None)) => estimate_program(&before, config).map(FileEstimate::Deleted),
+            (Ok(None), Ok(Some(after))) => estimate_program(&after, config).map(FileEstimate::Added),
+            (Ok(Some(before)), Ok(Some(after))) => estimate_program_diff(&before, &after, config, thresholds)
+                .map(|diff| FileEstimate::Modified(Box::new(diff))),
+            (Err(error), _) | (_, Err(error)) => Err(error),
+        };
+        let change = match &estimate {
+            Ok(FileEstimate::Added(_)) => FileChange::Added,
+            Ok(FileEstimate::Deleted(_)) => FileChange::Deleted,
+            Ok(FileEstimate::Modified(_)) => FileChange::Modified,
+            Err(_) => match (before.contains(&unit), after.contains(&unit)) {
+                (false, _) => FileChange::Added,
+                (_, false) => FileChange::Deleted,
+                _ => FileChange::Modified,
+            },
+        };
+        files.push(FileDiff {
+            path: unit.path().to_string(),
+            change,
+            estimate,
+        });
+    }
+    Ok(files)
+}
+
+/// What a changed file is estimated as: itself, or the project with the given manifest.
+#[derive(PartialEq)]
+enum Unit {
+    File(String),
+    Project(String),
+}
+
+impl Unit {
+    fn path(&self) -> &str {
+        match self {
+            Unit::File(path) | Unit::Project(path) => path,
+        }
+    }
+}
+
+/**
+    The files of a revision. Projects are loaded from disk, so the revision's Q# sources and
+    manifests are written to a temporary directory the first time a project is needed.
+    Files are read from the commit the revision named when the tree was made, and named
+    after the revision in diagnostics.
+**/
+struct Tree<'a> {
+    repo: &'a Path,
+    revision: &'a str,
+    commit: String,
+    paths: HashSet<String>,
+    checkout: Option<tempfile::TempDir>,
+}
+
+impl<'a> Tree<'a> {
+    fn new(repo: &'a Path, revision: &'a str) -> Result<Self> {
+        let commit = commit(repo, revision)?;
+        let output = git(repo, &["ls-tree", "-r", "-z", "--name-only", &commit])?;
+        Ok(Tree {
+            repo,
+            revision,
+            commit,
+            paths: output.split('\0').filter(|path| !path.is_empty()).map(String::from).collect(),
+            checkout: None,
+        })
+    }
+
+    fn contains(&self, unit: &Unit) -> bool {
+        self.paths.contains(unit.path())
+    }
+
+    /// The manifest of the innermost project `path` is in, e.g. `samples/qft/qsharp.json`.
+    fn manifest_of(&self, path: &str) -> Option<String> {
+        manifests_above(path).find(|manifest| self.paths.contains(manifest))
+    }
+
+    /// The unit as of this revision, or `None` if it does not exist or has no entry point.
+    fn program(&mut self, unit: &Unit) -> Result<Option<Program>> {
+        if !self.contains(unit) {
+            return Ok(None);
+        }
+        let program = match unit {
+            Unit::File(path) => {
+                let contents = blob(self.repo, &self.commit, path)?;
+                Program::from_source(format!("{}:{path}", self.revision).into(), contents.into())
+            }
+            Unit::Project(manifest) => {
+                let mut program = Program::from_path(self.checkout()?.join(manifest))?;
+                program.name = format!("{}:{manifest}", self.revision).into();
+                program
+            }
+        };
+        Ok(Some(program).filter(has_entry_point))
+    }
+
+    fn checkout(&mut self) -> Result<&Path> {
+        if self.checkout.is_none() {
+            let directory = tempfile::tempdir().map_err(|e| Error::read(self.revision, e))?;
+            let sources = self
+                .paths
+                .iter()
+                .filter(|path| path.ends_with(".qs") || path.rsplit('/').next() == Some(MANIFEST_FILE));
+            for path in sources {
+                let object = format!("{}:{path}", self.revision);
+                let contents = blob(self.repo, &self.commit, path)?;
+                let target = directory.path().join(path);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent).map_err(|e| Error::read(&object, e))?;
+                }
+                fs::write(&target, contents).map_err(|e| Error::read(&object, e))?;
+            }
+            self.checkout = Some(directory);
+        }
+        Ok(self.checkout.as_ref().expect("the revision was just checked out").path())
+    }
+}
+
+/// Where the manifests of the projects `path` could be in are, innermost first.
+fn manifests_above(path: &str) -> impl Iterator<Item = String> + '_ {
+    path.match_indices('/')
+        .map(|(index, _)| &path[..=index])
+        .rev()
+        .chain([""])
+        .map(|directory| format!("{directory}{MANIFEST_FILE}"))
+}
+
+/**
+    Whether any of the program's own sources declares an `@EntryPoint()`; an entry
+    expression cannot be given for changed files, so without one there is nothing to run.
+    The sources are parsed rather than searched, so an attribute in a comment or string
+    does not count. Parse errors are left to the estimate to report.
+**/
+fn has_entry_point(program: &Program) -> bool {
+    let root = &program.package_graph_sources.root;
+    let language_features = qsc::LanguageFeatures::from_iter(root.language_features.iter().cloned());
+    root.sources.iter().any(|(_, contents)| {
+        let (nodes, _) = qsc::parse::top_level_nodes(contents, language_features);
+        nodes.iter().flat_map(items).any(|item| item.attrs.iter().any(|attr| &*attr.name.name == "EntryPoint"))
+    })
+}
+
+/// The items declared at the top level of a source: in its namespaces, or directly in a
+/// file without a namespace block.
+fn items(node: &TopLevelNode) -> Vec<&Item> {
+    match node {
+        TopLevelNode::Namespace(namespace) => namespace.items.iter().map(AsRef::as_ref).collect(),
+        TopLevelNode::Stmt(stmt) => match &*stmt.kind {
+            StmtKind::Item(item) => vec![item],
+            _ => Vec::new(),
+        },
+    }
+}
+
+/**
+    The Q# files and project manifests changed between two revisions. Renames count as a
+    deletion and an addition, since the two programs are estimated separately anyway.
+**/
+pub fn changed_files(repo: &Path, base: &str, head: &str) -> Result<Vec<(FileChange, String)>> {
+    let (base, head) = (commit(repo, base)?, commit(repo, head)?);
+    let nested_manifests = format!("*/{MANIFEST_FILE}");
+    let output = git(
+        repo,
+        &["diff", "--name-status", "--no-renames", "-z", &base, &head, "--", "*.qs", MANIFEST_FILE, &nested_manifests],
+    )?;
+
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+    let mut files = Vec::new();
+    while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+        let change = match status {
+            "A" => FileChange::Added,
+            "D" => FileChange::Deleted,
+            _ => FileChange::Modified,
+        };
+        files.push((change, path.to_string()));
+    }
+    Ok(files)
+}
+
+/**
+    The file at `path` as of `revision`, named `revision:path` in diagnostics.
+**/
+pub fn program_at(repo: &Path, revision: &str, path: &str) -> Result<Program> {
+    let contents = blob(repo, &commit(repo, revision)?, path)?;
+    Ok(Program::from_source(format!("{revision}:{path}").into(), contents.into()))
+}
+
+/**
+    The commit `revision` names, e.g. `main` or `HEAD~1`. Revisions are resolved before
+    anything else is asked of git, so one that is not a commit, or starts with `-` and
+    would pass for an option, is reported as such.
+**/
+fn commit(repo: &Path, revision: &str) -> Result<String> {
+    let commit = format!("{revision}^{{commit}}");
+    Ok(git(repo, &["rev-parse", "--verify", "--end-of-options", &commit])?.trim().to_string())
+}
+
+fn blob(repo: &Path, commit: &str, path: &str) -> Result<String> {
+    git(repo, &["cat-file", "blob", &format!("{commit}:{path}")])
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let failed = |message: String| Error::Git {
+        repo: repo.display().to_string(),
+        command: args.join(" "),
+        message,
+    };
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|e| failed(format!("could not run git: {e}")))?;
+    if !output.status.success() {
+        return Err(failed(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    String::from_utf8(output.stdout).map_err(|e| failed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::{changed_files, commit, git, manifests_above, FileChange};
+    use crate::Error;
+
+    #[test]
+    fn looks_for_manifests_from_the_innermost_directory() {
+        let manifests: Vec<String> = manifests_above("samples/qft/src/Main.qs").collect();
+        assert_eq!(
+            manifests,
+            ["samples/qft/src/qsharp.json", "samples/qft/qsharp.json", "samples/qsharp.json", "qsharp.json"]
+        );
+        assert_eq!(manifests_above("Main.qs").collect::<Vec<_>>(), ["qsharp.json"]);
+    }
+
+    #[test]
+    fn lists_changed_sources_and_manifests() {
+        let repo = tempfile::tempdir().unwrap();
+        let commit = |message: &str| {
+            git(repo.path(), &["add", "-A"]).unwrap();
+            git(repo.path(), &["-c", "user.name=test", "-c", "user.email=test@example.com", "commit", "-qm", message]).unwrap();
+        };
+        git(repo.path(), &["init", "-q"]).unwrap();
+        fs::create_dir(repo.path().join("lib")).unwrap();
+        fs::write(repo.path().join("Old.qs"), "").unwrap();
+        fs::write(repo.path().join("Kept.qs"), "").unwrap();
+        fs::write(repo.path().join("README.md"), "").unwrap();
+        commit("base");
+
+        fs::remove_file(repo.path().join("Old.qs")).unwrap();
+        fs::write(repo.path().join("Kept.qs"), "namespace Kept {}").unwrap();
+        fs::write(repo.path().join("lib/qsharp.json"), "{}").unwrap();
+        fs::write(repo.path().join("README.md"), "changed").unwrap();
+        commit("head");
+
+        let mut files = changed_files(repo.path(), "HEAD~1", "HEAD").unwrap();
+        files.sort_by(|(_, a), (_, b)| a.cmp(b));
+        assert_eq!(
+            files,
+            [
+                (FileChange::Modified, "Kept.qs".to_string()),
+                (FileChange::Deleted, "Old.qs".to_string()),
+                (FileChange::Added, "lib/qsharp.json".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_accepts_revisions_naming_a_commit() {
+        let repo = tempfile::tempdir().unwrap();
+        git(repo.path(), &["init", "-q"]).unwrap();
+        fs::write(repo.path().join("Main.qs"), "").unwrap();
+        git(repo.path(), &["add", "-A"]).unwrap();
+        git(repo.path(), &["-c", "user.name=test", "-c", "user.email=test@example.com", "commit", "-qm", "base"]).unwrap();
+
+        let head = git(repo.path(), &["rev-parse", "HEAD"]).unwrap();
+        assert_eq!(commit(repo.path(), "HEAD").unwrap(), head.trim());
+        for revision in ["--output=diff.txt", "-p", "no-such-branch", "HEAD:Main.qs"] {
+            let result = changed_files(repo.path(), revision, "HEAD");
+            assert!(matches!(result, Err(Error::Git { .. })), "{revision}");
+        }
+        assert!(!repo.path().join("diff.txt").exists());
+    }
+}
@@ -1,83 +1,46 @@
-use std::{fs, io};
-use std::io::{
-    Read
-};
+pub mod budget;
+pub mod cache;
+pub mod catalog;
+pub mod config;
+pub mod diff;
+pub mod error;
+pub mod format;
+pub mod formula;
+pub mod git;
+pub mod output;
+pub mod program;
+pub mod registry;
+pub mod sensitivity;
+pub mod sweep;
+
 use std::path::Path;
 use std::sync::Arc;
 use qsc_frontend::compile::{SourceContents, SourceName};
-use miette::{Context, IntoDiagnostic};
 use qsc::{interpret, PackageType};
 use qsc::packages::BuildableProgram;
 use qsc::target::Profile;
-use qsc_project::{PackageGraphSources, Project};
-use resource_estimator::estimate_entry;
-use miette::Result;
-use serde::{Deserialize, Serialize};
-
-#[derive(Serialize, Deserialize)]
-pub struct EstimationConfig {
-    label: String,
-    detail: String,
-    params: Params,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct Params {
-    #[serde(rename = "qubitParams")]
-    qubit_params: QubitParams,
-    #[serde(rename = "qecScheme")]
-    qec_scheme: QecScheme,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct QubitParams {
-    name: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct QecScheme {
-    name: String,
-}
-
-pub fn default_estimation_config() -> EstimationConfig {
-    EstimationConfig {
-        label: "qubit_maj_ns_e6 + surface_code".to_string(),
-        detail: "Majorana qubit with 1e-6 error rate (surface code QEC)".to_string(),
-        params: Params {
-            qubit_params: QubitParams {
-                name: "qubit_maj_ns_e6".to_string(),
-            },
-            qec_scheme: QecScheme {
-                name: "surface_code".to_string(),
-            },
-        },
-    }
-}
-
-fn read_source(path: impl AsRef<Path>) -> miette::Result<(SourceName, SourceContents)> {
-    let path = path.as_ref();
-    if path.as_os_str() == "-" {
-        let mut input = String::new();
-        io::stdin()
-            .read_to_string(&mut input)
-            .into_diagnostic()
-            .context("could not read standard input")?;
+use qsc_project::PackageGraphSources;
+use resource_estimator::{estimate_entry, estimate_physical_resources_from_json};
+use serde::Serialize;
 
-        Ok(("<stdin>".into(), input.into()))
-    } else {
-        let contents = fs::read_to_string(path)
-            .into_diagnostic()
-            .with_context(|| format!("could not read source file `{}`", path.display()))?;
-
-        Ok((path.to_string_lossy().into(), contents.into()))
-    }
-}
+pub use budget::{Budget, LimitCheck};
+pub use cache::ResultCache;
+pub use config::*;
+pub use diff::{EstimationDiff, Thresholds};
+pub use error::{Error, ErrorDiagnostic, Result, SourceLocation};
+pub use format::OutputFormat;
+pub use output::{EstimationOutput, FrontierOutput, LogicalCounts};
+pub use program::Program;
+pub use registry::{HardwareProfile, Registry};
+pub use sensitivity::{Elasticity, Sensitivity};
+pub use sweep::{SweepPoint, SweepSpec};
 
 #[allow(clippy::type_complexity)]
 #[allow(clippy::needless_pass_by_value)]
 pub fn project_to_qsc_args(
     package_graph_sources: PackageGraphSources,
     entry: Option<String>,
+    profile: Profile,
 ) -> Result<
     (
         qsc::SourceMap,
@@ -88,18 +51,10 @@ pub fn project_to_qsc_args(
     ),
     Vec<qsc::compile::Error>,
 > {
-    /**
-        We're only going to use the AdaptiveRI profile for now.
-    **/
-    let capabilities = qsc::TargetCapabilityFlags::from(Profile::AdaptiveRI);
-
-    let pkg_graph: PackageGraphSources = package_graph_sources.into();
-    let pkg_graph: qsc_project::PackageGraphSources = pkg_graph.into();
+    let capabilities = qsc::TargetCapabilityFlags::from(profile);
 
-    /**
-        This function call builds all dependencies as a part of preparing the package store for building the user code.
-    **/
-    let buildable_program = BuildableProgram::new(capabilities, pkg_graph);
+    // This function call builds all dependencies as a part of preparing the package store for building the user code.
+    let buildable_program = BuildableProgram::new(capabilities, package_graph_sources);
 
     if !buildable_program.dependency_errors.is_empty() {
         return Err(buildable_program.dependency_errors);
@@ -124,42 +79,498 @@ pub fn project_to_qsc_args(
     ))
 }
 
-pub fn estimate(file_path: impl AsRef<Path>) -> Result<String> {
-    let (source_name, source_contents) = read_source(file_path)?;
+/**
+    Estimates a Q# file, or a project given as a directory with a `qsharp.json` manifest.
+**/
+pub fn estimate(path: impl AsRef<Path>) -> Result<EstimationOutput> {
+    estimate_with_config(path, &default_estimation_config())
+}
+
+pub fn estimate_with_config(path: impl AsRef<Path>, config: &EstimationConfig) -> Result<EstimationOutput> {
+    estimate_program(&Program::from_path(path)?, config)
+}
+
+/**
+    Estimates a program whose source is already in memory, e.g. a file fetched by the API.
+**/
+pub fn estimate_source(
+    source_name: SourceName,
+    source_contents: SourceContents,
+    config: &EstimationConfig,
+) -> Result<EstimationOutput> {
+    estimate_program(&Program::from_source(source_name, source_contents), config)
+}
+
+pub fn estimate_program(program: &Program, config: &EstimationConfig) -> Result<EstimationOutput> {
+    estimate_program_batch(program, std::slice::from_ref(config))?
+        .pop()
+        .expect("the estimator returns one result per configuration")
+}
+
+pub fn estimate_batch(path: impl AsRef<Path>, configs: &[EstimationConfig]) -> Result<Vec<Result<EstimationOutput>>> {
+    estimate_program_batch(&Program::from_path(path)?, configs)
+}
+
+pub fn estimate_batch_source(
+    source_name: SourceName,
+    source_contents: SourceContents,
+    configs: &[EstimationConfig],
+) -> Result<Vec<Result<EstimationOutput>>> {
+    estimate_program_batch(&Program::from_source(source_name, source_contents), configs)
+}
+
+/**
+    Estimates one program under several configurations, like the `items` of an Azure
+    estimation job. The program is compiled and its logical counts traced once; each
+    configuration then gets its own result, so one invalid or infeasible item does not
+    fail the rest.
+**/
+pub fn estimate_program_batch(program: &Program, configs: &[EstimationConfig]) -> Result<Vec<Result<EstimationOutput>>> {
+    // The program is compiled per target profile, so configurations sharing a profile are
+    // estimated together and their results put back in the original order.
+    let mut profiles: Vec<TargetProfile> = Vec::new();
+    for config in configs {
+        if !profiles.contains(&config.profile) {
+            profiles.push(config.profile);
+        }
+    }
+
+    let mut outputs: Vec<Option<Result<EstimationOutput>>> = configs.iter().map(|_| None).collect();
+    for profile in profiles {
+        let (indices, group): (Vec<usize>, Vec<&EstimationConfig>) = configs
+            .iter()
+            .enumerate()
+            .filter(|(_, config)| config.profile == profile)
+            .unzip();
+        let items = run_estimator(&group, EstimateType::SinglePoint, |params, valid| {
+            run_program(program, profile, params, valid)
+        })?;
+        for (index, output) in indices.into_iter().zip(into_outputs(items)) {
+            outputs[index] = Some(output);
+        }
+    }
+
+    Ok(outputs
+        .into_iter()
+        .map(|output| output.expect("every configuration belongs to one profile"))
+        .collect())
+}
+
+/**
+    Estimates every point of a parameter sweep, handing each to `on_point` as soon as it
+    is done. The program is compiled and its logical counts traced once, with the first
+    point that estimates successfully; the other points only need those counts and are
+    estimated in parallel. Returns the number of points.
 
-    let project_config = Project::from_single_file(
-        Arc::from(source_name.as_ref()),
-        source_contents
-    );
+    Failing points, e.g. an incompatible qubit model and QEC scheme, are reported as
+    points with an error; only a program that fails to compile or run fails the sweep.
+**/
+pub fn estimate_program_sweep(program: &Program, spec: &SweepSpec, mut on_point: impl FnMut(SweepPoint)) -> Result<usize> {
+    let mut configs = spec.configs().into_iter().enumerate();
+    let total = configs.len();
+    let mut interpreter = build_interpreter(program, spec.profile)?;
+
+    let mut counts = None;
+    for (index, config) in configs.by_ref() {
+        let output = run_estimator(&[&config], EstimateType::SinglePoint, |params, valid| {
+            run_entry(&mut interpreter, program, params, valid)
+        })
+        .and_then(|items| into_outputs(items).pop().expect("the estimator returns one result per configuration"));
+        match output {
+            Err(error @ (Error::Config { .. } | Error::Estimation { .. })) => on_point(SweepPoint {
+                index,
+                config,
+                output: Err(error),
+            }),
+            Err(error) => return Err(error),
+            Ok(output) => {
+                counts = Some(output.logical_counts.clone());
+                on_point(SweepPoint {
+                    index,
+                    config,
+                    output: Ok(output),
+                });
+                break;
+            }
+        }
+    }
+
+    if let Some(counts) = counts {
+        sweep::run(spec, configs.collect(), |config| estimate_logical_counts(&counts, config), on_point);
+    }
+    Ok(total)
+}
+
+/**
+    Ranks the qubit and QEC parameters of `config` by how much the program's physical
+    qubits and runtime change with each; see `sensitivity::analyze`. The program is only
+    traced once, for the baseline estimate.
+**/
+pub fn estimate_program_sensitivity(program: &Program, config: &EstimationConfig, step: f64) -> Result<Sensitivity> {
+    sensitivity::analyze(estimate_program(program, config)?, config, step)
+}
+
+pub fn estimate_logical_counts_sensitivity(counts: &LogicalCounts, config: &EstimationConfig, step: f64) -> Result<Sensitivity> {
+    sensitivity::analyze(estimate_logical_counts(counts, config)?, config, step)
+}
+
+/**
+    Estimates two versions of a program under the same configuration and compares every
+    count, e.g. to review what a change costs.
+**/
+pub fn estimate_program_diff(
+    before: &Program,
+    after: &Program,
+    config: &EstimationConfig,
+    thresholds: &Thresholds,
+) -> Result<EstimationDiff> {
+    thresholds.validate().map_err(|report| Error::config("thresholds", &report))?;
+    Ok(EstimationDiff::new(
+        estimate_program(before, config)?,
+        estimate_program(after, config)?,
+        thresholds,
+    ))
+}
+
+/**
+    Estimates one program under two configurations, compiled once, and compares every count.
+**/
+pub fn estimate_config_diff(
+    program: &Program,
+    before: &EstimationConfig,
+    after: &EstimationConfig,
+    thresholds: &Thresholds,
+) -> Result<EstimationDiff> {
+    thresholds.validate().map_err(|report| Error::config("thresholds", &report))?;
+    let mut outputs = estimate_program_batch(program, &[before.clone(), after.clone()])?.into_iter();
+    let (Some(before), Some(after)) = (outputs.next(), outputs.next()) else {
+        unreachable!("the estimator returns one result per configuration");
+    };
+    Ok(EstimationDiff::new(before?, after?, thresholds))
+}
+
+pub fn estimate_frontier(path: impl AsRef<Path>, config: &EstimationConfig) -> Result<FrontierOutput> {
+    estimate_program_frontier(&Program::from_path(path)?, config)
+}
+
+pub fn estimate_frontier_source(
+    source_name: SourceName,
+    source_contents: SourceContents,
+    config: &EstimationConfig,
+) -> Result<FrontierOutput> {
+    estimate_program_frontier(&Program::from_source(source_name, source_contents), config)
+}
+
+/**
+    Estimates the space/time frontier instead of a single point: every Pareto-optimal
+    combination of physical qubits and runtime the estimator finds by trading T factories
+    against runtime.
+**/
+pub fn estimate_program_frontier(program: &Program, config: &EstimationConfig) -> Result<FrontierOutput> {
+    let configs = [config];
+    let items = run_estimator(&configs, EstimateType::Frontier, |params, valid| {
+        run_program(program, config.profile, params, valid)
+    })?;
+    into_frontier(items)
+}
+
+/**
+    Estimates an algorithm known only by its logical counts, e.g. from a paper, without
+    compiling any Q# program.
+**/
+pub fn estimate_logical_counts(counts: &LogicalCounts, config: &EstimationConfig) -> Result<EstimationOutput> {
+    estimate_batch_logical_counts(counts, std::slice::from_ref(config))?
+        .pop()
+        .expect("the estimator returns one result per configuration")
+}
+
+pub fn estimate_batch_logical_counts(
+    counts: &LogicalCounts,
+    configs: &[EstimationConfig],
+) -> Result<Vec<Result<EstimationOutput>>> {
+    let configs: Vec<&EstimationConfig> = configs.iter().collect();
+    let items = run_estimator(&configs, EstimateType::SinglePoint, |params, valid| {
+        estimate_counts(counts, params, valid)
+    })?;
+    Ok(into_outputs(items))
+}
 
+pub fn estimate_frontier_logical_counts(counts: &LogicalCounts, config: &EstimationConfig) -> Result<FrontierOutput> {
+    let configs = [config];
+    let items = run_estimator(&configs, EstimateType::Frontier, |params, valid| {
+        estimate_counts(counts, params, valid)
+    })?;
+    into_frontier(items)
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum EstimateType {
+    SinglePoint,
+    Frontier,
+}
+
+/**
+    Job params as the estimator reads them; labels and details are ours only.
+**/
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobParams<'a> {
+    #[serde(flatten)]
+    params: &'a Params,
+    estimate_type: EstimateType,
+}
+
+/**
+    Validates every configuration, hands the job params of the valid ones to `estimate` in
+    one call, together with those configurations, and splits the answer into each item's
+    JSON or the estimator's error for it.
+    An invalid configuration gets its validation error as its item, like an infeasible one.
+**/
+fn run_estimator(
+    configs: &[&EstimationConfig],
+    estimate_type: EstimateType,
+    estimate: impl FnOnce(&str, &[&EstimationConfig]) -> Result<String>,
+) -> Result<Vec<Result<serde_json::Value>>> {
+    let mut items: Vec<Option<Result<serde_json::Value>>> = configs
+        .iter()
+        .map(|config| {
+            if let Some(name) = &config.hardware {
+                return Some(Err(Error::Config {
+                    label: config.label.clone(),
+                    message: format!("hardware profile `{name}` has not been resolved through a profile registry"),
+                }));
+            }
+            let report = config.params.validate().err()?;
+            Some(Err(Error::config(&config.label, &report)))
+        })
+        .collect();
+
+    let valid: Vec<&EstimationConfig> = configs
+        .iter()
+        .zip(&items)
+        .filter(|(_, item)| item.is_none())
+        .map(|(config, _)| *config)
+        .collect();
+    if !valid.is_empty() {
+        let job_params: Vec<JobParams> = valid
+            .iter()
+            .map(|config| JobParams { params: &config.params, estimate_type })
+            .collect();
+        let input_json = serde_json::to_string(&job_params).expect("job params serialize to JSON");
+        let estimation_result = estimate(&input_json, &valid)?;
+
+        let estimated = output::split_items(&estimation_result).map_err(|e| Error::Output(e.to_string()))?;
+        if estimated.len() != valid.len() {
+            return Err(Error::Output(format!(
+                "{} results for {} configurations",
+                estimated.len(),
+                valid.len()
+            )));
+        }
+
+        let mut estimated = estimated.into_iter().zip(&valid);
+        for item in items.iter_mut().filter(|item| item.is_none()) {
+            let (result, config) = estimated.next().expect("one result per valid configuration");
+            *item = Some(result.map_err(|message| estimation_error(message, std::slice::from_ref(config))));
+        }
+    }
+
+    Ok(items
+        .into_iter()
+        .map(|item| item.expect("every configuration is validated or estimated"))
+        .collect())
+}
+
+/**
+    Compiles the program for `profile`, traces its logical counts and estimates them under `params`.
+**/
+fn run_program(program: &Program, profile: TargetProfile, params: &str, configs: &[&EstimationConfig]) -> Result<String> {
+    let mut interpreter = build_interpreter(program, profile)?;
+    run_entry(&mut interpreter, program, params, configs)
+}
+
+fn run_entry(
+    interpreter: &mut interpret::Interpreter,
+    program: &Program,
+    params: &str,
+    configs: &[&EstimationConfig],
+) -> Result<String> {
+    estimate_entry(interpreter, params).map_err(|errors| {
+        // Interpreter errors carry the program's source spans, so they are kept whole; the
+        // estimator's own errors only matter when the program ran.
+        let (interpreter_errors, estimation_errors): (Vec<_>, Vec<_>) = errors
+            .into_iter()
+            .partition(|e| matches!(e, resource_estimator::Error::Interpreter(_)));
+        if interpreter_errors.is_empty() {
+            let messages: Vec<String> = estimation_errors.iter().map(ToString::to_string).collect();
+            estimation_error(messages.join("; "), configs)
+        } else {
+            Error::Interpreter {
+                program: program.name.to_string(),
+                errors: interpreter_errors
+                    .into_iter()
+                    .filter_map(|e| match e {
+                        resource_estimator::Error::Interpreter(e) => Some(e),
+                        resource_estimator::Error::Estimation(_) => None,
+                    })
+                    .collect(),
+            }
+        }
+    })
+}
+
+fn estimate_counts(counts: &LogicalCounts, params: &str, configs: &[&EstimationConfig]) -> Result<String> {
+    if counts.num_qubits == 0 {
+        return Err(Error::Counts("numQubits must be positive".to_string()));
+    }
+    let logical_resources = serde_json::to_string(counts).expect("logical counts serialize to JSON");
+
+    estimate_physical_resources_from_json(&logical_resources, params).map_err(|e| estimation_error(e, configs))
+}
+
+fn into_outputs(items: Vec<Result<serde_json::Value>>) -> Vec<Result<EstimationOutput>> {
+    items
+        .into_iter()
+        .map(|item| {
+            EstimationOutput::from_value(item?).map_err(|e| Error::Output(e.to_string()))
+        })
+        .collect()
+}
+
+fn into_frontier(mut items: Vec<Result<serde_json::Value>>) -> Result<FrontierOutput> {
+    let item = items
+        .pop()
+        .expect("the estimator returns one result per configuration")?;
+
+    FrontierOutput::from_value(item).map_err(|e| Error::Output(e.to_string()))
+}
+
+fn build_interpreter(program: &Program, profile: TargetProfile) -> Result<interpret::Interpreter> {
+    // The standard library is compiled again for every interpreter, and dominates the cost
+    // of an estimation. It cannot be compiled once and shared: `Interpreter::new` takes
+    // ownership of the package store and adds the user code to it, and the store is not
+    // `Send`, since the compiler's syntax trees hold `Rc`s. `core/benches/stdlib.rs`
+    // measures what this costs back to back and under concurrent requests.
     let (source_map, capabilities, language_features, store, deps) =
-        project_to_qsc_args(project_config.package_graph_sources, None)
-            .map_err(|e| miette::Error::msg(format!("QSC argument conversion error: {:?}", e)))?;
+        project_to_qsc_args(program.package_graph_sources.clone(), program.entry.clone(), profile.into())
+            .map_err(|errors| compile_error(program, profile, errors.into_iter().map(interpret::Error::Compile).collect()))?;
 
-    let mut interpreter = interpret::Interpreter::new(
+    interpret::Interpreter::new(
         source_map,
         PackageType::Exe,
         capabilities,
         language_features,
         store,
         &deps[..],
-    ).map_err(|e| miette::Error::msg(format!("Interpreter creation error: {:?}", e)))?;
-
-    let input_json =  serde_json::to_string(&default_estimation_config()).unwrap();
-    let estimation_result = estimate_entry(&mut interpreter, &input_json)
-        .map_err(|e| match &e[0] {
-            resource_estimator::Error::Interpreter(interpret::Error::Eval(e)) => miette::Error::msg(e.to_string()),
-            resource_estimator::Error::Interpreter(_) => miette::Error::msg("Unexpected interpreter error"),
-            resource_estimator::Error::Estimation(e) => miette::Error::msg(e.to_string()),
-    })?;
+    ).map_err(|errors| compile_error(program, profile, errors))
+}
+
+/**
+    Keeps every compile error, and for the restricted profiles explains that the program
+    may simply use constructs the profile does not allow.
+**/
+fn compile_error(program: &Program, profile: TargetProfile, errors: Vec<interpret::Error>) -> Error {
+    let help = (profile != TargetProfile::Unrestricted).then(|| {
+        format!(
+            "the `{profile}` profile supports {}; programs using more than that are rejected under it. \
+             Select a less restrictive profile (e.g. `unrestricted`) to estimate them.",
+            profile.description()
+        )
+    });
+
+    Error::Compile {
+        program: program.name.to_string(),
+        profile,
+        errors,
+        help,
+    }
+}
+
+/**
+    Attributes an estimator error to its configuration; for a batch, to all of them.
+**/
+fn estimation_error(message: impl std::fmt::Display, configs: &[&EstimationConfig]) -> Error {
+    let labels: Vec<&str> = configs.iter().map(|config| config.label.as_str()).collect();
+    let constraints = match configs {
+        [config] => config.params.constraints.as_ref().map(ToString::to_string),
+        _ => None,
+    };
+
+    Error::Estimation {
+        label: labels.join(", "),
+        message: message.to_string(),
+        constraints,
+    }
+}
+
+/**
+    Reads the file at `path` as TOML if it is named `*.toml`, otherwise as JSON, like budget
+    files, sweep specs and registries are. `invalid` turns a parse error into the caller's
+    error.
+**/
+pub(crate) fn load_toml_or_json<T: serde::de::DeserializeOwned>(path: &Path, invalid: impl Fn(String) -> Error) -> Result<T> {
+    let contents = std::fs::read_to_string(path).map_err(|e| Error::read(path.display(), e))?;
+    if path.extension().is_some_and(|extension| extension == "toml") {
+        toml::from_str(&contents).map_err(|e| invalid(e.to_string()))
+    } else {
+        serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))
+    }
+}
 
-    Ok(estimation_result)
+pub async fn fetch_qs_file(file_url: &str) -> Result<String> {
+    let response = reqwest::get(file_url)
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| Error::read(file_url, e))?;
+
+    response.text().await.map_err(|e| Error::read(file_url, e))
 }
 
-pub async fn fetch_qs_file(file_url: &str) -> String {
-    let target = "https://www.rust-lang.org/logos/rust-logo-512x512.png";
-    let response = reqwest::get(target).await;
-    let content = response.unwrap().text().await;
-    println!("content {:?}", content);
-    content.unwrap().to_string()
+#[cfg(test)]
+mod tests {
+    use super::{default_estimation_config, run_estimator, Error, ErrorBudget, EstimateType, EstimationConfig};
+
+    #[test]
+    fn gives_invalid_configurations_their_own_failed_item() {
+        let valid = default_estimation_config();
+        let mut invalid = EstimationConfig {
+            label: "invalid".to_string(),
+            ..default_estimation_config()
+        };
+        invalid.params.error_budget = Some(ErrorBudget::Total(2.0));
+
+        let mut estimated = Vec::new();
+        let items = run_estimator(&[&invalid, &valid, &invalid], EstimateType::SinglePoint, |params, configs| {
+            estimated = configs.iter().map(|config| config.label.clone()).collect();
+            assert_eq!(serde_json::from_str::<serde_json::Value>(params).unwrap().as_array().unwrap().len(), 1);
+            Ok(r#"[{"status": "success"}]"#.to_string())
+        })
+        .unwrap();
+
+        assert_eq!(estimated, [valid.label]);
+        assert!(matches!(&items[0], Err(Error::Config { label, .. }) if label == "invalid"));
+        assert_eq!(items[1].as_ref().unwrap()["status"], "success");
+        assert!(matches!(&items[2], Err(Error::Config { .. })));
+    }
+
+    #[test]
+    fn does_not_run_the_estimator_without_valid_configurations() {
+        let mut invalid = default_estimation_config();
+        invalid.params.error_budget = Some(ErrorBudget::Total(0.0));
+
+        let items = run_estimator(&[&invalid], EstimateType::SinglePoint, |_, _| unreachable!()).unwrap();
+        assert!(matches!(&items[..], [Err(Error::Config { .. })]));
+    }
+
+    #[test]
+    fn rejects_hardware_profiles_that_were_not_resolved() {
+        let unresolved = EstimationConfig {
+            hardware: Some("ourlab-2027".to_string()),
+            ..default_estimation_config()
+        };
+
+        let items = run_estimator(&[&unresolved], EstimateType::SinglePoint, |_, _| unreachable!()).unwrap();
+        assert!(matches!(&items[..], [Err(Error::Config { message, .. })] if message.contains("`ourlab-2027`")));
+    }
 }
@@ -0,0 +1,481 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/**
+    Typed view of the resource estimator's JSON output. Counts are kept as `u64`
+    since runtimes (in nanoseconds) and depths easily exceed 32 bits.
+**/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimationOutput {
+    pub status: String,
+    pub job_params: JobParams,
+    pub physical_counts: PhysicalCounts,
+    pub physical_counts_formatted: PhysicalCountsFormatted,
+    pub logical_qubit: LogicalQubit,
+    /// Missing when the program needs no T states.
+    #[serde(default)]
+    pub tfactory: Option<TFactory>,
+    pub error_budget: ErrorBudget,
+    pub logical_counts: LogicalCounts,
+    pub report_data: ReportData,
+    /// Whether this result came from a `ResultCache` instead of the estimator; serialized as
+    /// `cacheHit` when it did. `raw_json` is the estimator's output and never has it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache_hit: bool,
+    #[serde(skip)]
+    raw: String,
+}
+
+impl EstimationOutput {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let mut output: Self = serde_json::from_str(json)?;
+        output.raw = json.to_string();
+        Ok(output)
+    }
+
+    pub(crate) fn from_value(value: serde_json::Value) -> serde_json::Result<Self> {
+        let raw = value.to_string();
+        let mut output: Self = serde_json::from_value(value)?;
+        output.raw = raw;
+        Ok(output)
+    }
+
+    /// The estimator's JSON for this result, for consumers that pass it through.
+    pub fn raw_json(&self) -> &str {
+        &self.raw
+    }
+
+    /// A header and one row of the physical counts, in the columns of `FrontierOutput::to_csv`.
+    pub fn to_csv(&self) -> String {
+        format!("{CSV_HEADER}{}", csv_row(&self.physical_counts, &self.logical_qubit))
+    }
+}
+
+/**
+    Result of a frontier estimation: the Pareto-optimal trade-offs between physical
+    qubits and runtime the estimator found by varying the number of T factories,
+    sorted by increasing runtime (and so decreasing physical qubits).
+**/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontierOutput {
+    pub status: String,
+    pub job_params: JobParams,
+    pub frontier_entries: Vec<FrontierEntry>,
+    pub logical_counts: LogicalCounts,
+    pub report_data: ReportData,
+    #[serde(skip)]
+    raw: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontierEntry {
+    pub logical_qubit: LogicalQubit,
+    #[serde(default)]
+    pub tfactory: Option<TFactory>,
+    pub error_budget: ErrorBudget,
+    pub physical_counts: PhysicalCounts,
+    pub physical_counts_formatted: PhysicalCountsFormatted,
+}
+
+impl FrontierOutput {
+    pub(crate) fn from_value(value: serde_json::Value) -> serde_json::Result<Self> {
+        let raw = value.to_string();
+        let mut output: Self = serde_json::from_value(value)?;
+        output.raw = raw;
+
+        // Keep only points no other point beats in both qubits and runtime.
+        output.frontier_entries.sort_by_key(|entry| {
+            (entry.physical_counts.runtime, entry.physical_counts.physical_qubits)
+        });
+        let mut fewest_qubits = u64::MAX;
+        output.frontier_entries.retain(|entry| {
+            let qubits = entry.physical_counts.physical_qubits;
+            let optimal = qubits < fewest_qubits;
+            fewest_qubits = fewest_qubits.min(qubits);
+            optimal
+        });
+
+        Ok(output)
+    }
+
+    pub fn raw_json(&self) -> &str {
+        &self.raw
+    }
+
+    /// One row per frontier point, for plotting the space/time trade-off.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(CSV_HEADER);
+        for entry in &self.frontier_entries {
+            csv.push_str(&csv_row(&entry.physical_counts, &entry.logical_qubit));
+        }
+        csv
+    }
+}
+
+/// Columns of the CSV rows of physical counts.
+const CSV_HEADER: &str = "physical_qubits,runtime_ns,rqops,code_distance,num_tfactories,\
+                          physical_qubits_for_algorithm,physical_qubits_for_tfactories,logical_depth\n";
+
+fn csv_row(counts: &PhysicalCounts, logical_qubit: &LogicalQubit) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{}\n",
+        counts.physical_qubits,
+        counts.runtime,
+        counts.rqops,
+        logical_qubit.code_distance,
+        counts.breakdown.num_tfactories,
+        counts.breakdown.physical_qubits_for_algorithm,
+        counts.breakdown.physical_qubits_for_tfactories,
+        counts.breakdown.logical_depth,
+    )
+}
+
+/**
+    The estimator answers a list of job params with a list of results. An item it
+    could not estimate (e.g. infeasible constraints) is `{"status": "error", "message": ...}`
+    and comes back as `Err(message)`.
+**/
+pub(crate) fn split_items(json: &str) -> serde_json::Result<Vec<Result<serde_json::Value, String>>> {
+    let items = match serde_json::from_str(json)? {
+        serde_json::Value::Array(items) => items,
+        item => vec![item],
+    };
+
+    Ok(items
+        .into_iter()
+        .map(|item| {
+            if item.get("status").and_then(serde_json::Value::as_str) == Some("error") {
+                let message = item
+                    .get("message")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or("estimation failed");
+                Err(message.to_string())
+            } else {
+                Ok(item)
+            }
+        })
+        .collect())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JobParams {
+    pub qec_scheme: QecScheme,
+    pub error_budget: config::ErrorBudget,
+    pub qubit_params: QubitParams,
+    pub constraints: Constraints,
+    pub estimate_type: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QecScheme {
+    pub name: String,
+    pub error_correction_threshold: f64,
+    pub crossing_prefactor: f64,
+    pub logical_cycle_time: String,
+    pub physical_qubits_per_logical_qubit: String,
+    pub max_code_distance: u64,
+}
+
+/**
+    Gate-based and Majorana qubits report different operation times, so the
+    fields only one of the two instruction sets uses are optional.
+**/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QubitParams {
+    pub instruction_set: String,
+    pub name: String,
+    pub one_qubit_measurement_time: String,
+    pub one_qubit_gate_time: Option<String>,
+    pub two_qubit_gate_time: Option<String>,
+    pub two_qubit_joint_measurement_time: Option<String>,
+    pub t_gate_time: String,
+    pub one_qubit_measurement_error_rate: MeasurementErrorRate,
+    pub one_qubit_gate_error_rate: Option<f64>,
+    pub two_qubit_gate_error_rate: Option<f64>,
+    pub two_qubit_joint_measurement_error_rate: Option<MeasurementErrorRate>,
+    pub t_gate_error_rate: f64,
+    pub idle_error_rate: f64,
+}
+
+/**
+    Measurement error rates are either a single number or split into process
+    and readout errors; a single number applies to both.
+**/
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(from = "RawMeasurementErrorRate")]
+pub struct MeasurementErrorRate {
+    pub process: f64,
+    pub readout: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawMeasurementErrorRate {
+    Uniform(f64),
+    Split { process: f64, readout: f64 },
+}
+
+impl From<RawMeasurementErrorRate> for MeasurementErrorRate {
+    fn from(raw: RawMeasurementErrorRate) -> Self {
+        match raw {
+            RawMeasurementErrorRate::Uniform(rate) => Self { process: rate, readout: rate },
+            RawMeasurementErrorRate::Split { process, readout } => Self { process, readout },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Constraints {
+    pub max_distillation_rounds: u64,
+    pub logical_depth_factor: Option<f64>,
+    #[serde(rename = "maxTFactories")]
+    pub max_t_factories: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub max_duration: Option<String>,
+    pub max_physical_qubits: Option<u64>,
+}
+
+/// The estimator may echo `maxDuration` back in nanoseconds rather than as the given string.
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Duration {
+        Nanoseconds(u64),
+        Text(String),
+    }
+
+    Ok(Option::<Duration>::deserialize(deserializer)?.map(|duration| match duration {
+        Duration::Nanoseconds(ns) => format!("{ns} ns"),
+        Duration::Text(text) => text,
+    }))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalCounts {
+    pub physical_qubits: u64,
+    pub runtime: u64,
+    pub rqops: u64,
+    pub breakdown: PhysicalCountsBreakdown,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalCountsBreakdown {
+    pub algorithmic_logical_qubits: u64,
+    pub algorithmic_logical_depth: u64,
+    pub logical_depth: u64,
+    pub num_tstates: u64,
+    pub clock_frequency: f64,
+    pub num_tfactories: u64,
+    pub num_tfactory_runs: u64,
+    pub physical_qubits_for_tfactories: u64,
+    pub physical_qubits_for_algorithm: u64,
+    pub required_logical_qubit_error_rate: f64,
+    pub required_logical_tstate_error_rate: Option<f64>,
+    pub num_ts_per_rotation: Option<u64>,
+    pub clifford_error_rate: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PhysicalCountsFormatted {
+    pub runtime: String,
+    pub rqops: String,
+    pub physical_qubits: String,
+    pub algorithmic_logical_qubits: String,
+    pub algorithmic_logical_depth: String,
+    pub logical_depth: String,
+    pub num_tstates: String,
+    pub num_tfactories: String,
+    pub num_tfactory_runs: String,
+    pub physical_qubits_for_algorithm: String,
+    pub physical_qubits_for_tfactories: String,
+    pub physical_qubits_for_tfactories_percentage: String,
+    pub required_logical_qubit_error_rate: String,
+    pub required_logical_tstate_error_rate: String,
+    pub physical_qubits_per_logical_qubit: String,
+    pub logical_cycle_time: String,
+    pub clock_frequency: String,
+    pub logical_error_rate: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogicalQubit {
+    pub code_distance: u64,
+    pub physical_qubits: u64,
+    pub logical_cycle_time: u64,
+    pub logical_error_rate: f64,
+}
+
+/**
+    The T factory the estimator picked. Each round runs `num_units_per_round[i]`
+    copies of the distillation unit `unit_name_per_round[i]`; a code distance of 1
+    means the round runs on physical qubits.
+**/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TFactory {
+    pub physical_qubits: u64,
+    pub runtime: u64,
+    pub num_tstates: u64,
+    pub num_input_tstates: u64,
+    pub num_rounds: u64,
+    pub num_units_per_round: Vec<u64>,
+    pub unit_name_per_round: Vec<String>,
+    pub code_distance_per_round: Vec<u64>,
+    pub physical_qubits_per_round: Vec<u64>,
+    pub runtime_per_round: Vec<u64>,
+    pub logical_error_rate: f64,
+}
+
+/**
+    The error budget split the estimator actually used. Parts the program does not
+    need (e.g. rotations when it has none) are reassigned, so this can differ from
+    the requested `JobParams::error_budget`.
+**/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ErrorBudget {
+    pub logical: f64,
+    pub tstates: f64,
+    pub rotations: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogicalCounts {
+    pub num_qubits: u64,
+    pub t_count: u64,
+    pub rotation_count: u64,
+    pub rotation_depth: u64,
+    pub ccz_count: u64,
+    pub ccix_count: u64,
+    pub measurement_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReportData {
+    pub groups: Vec<ReportGroup>,
+    pub assumptions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportGroup {
+    pub title: String,
+    pub always_visible: bool,
+    pub entries: Vec<ReportEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReportEntry {
+    pub path: String,
+    pub label: String,
+    pub description: String,
+    pub explanation: String,
+}
+
+/// An estimate of a small program on `qubit_gate_ns_e3` and the surface code, for tests
+/// that need an `EstimationOutput` without running the estimator.
+#[cfg(test)]
+pub(crate) fn sample() -> EstimationOutput {
+    EstimationOutput::from_value(serde_json::json!({
+        "status": "success",
+        "jobParams": {
+            "qecScheme": {
+                "name": "surface_code",
+                "errorCorrectionThreshold": 0.01,
+                "crossingPrefactor": 0.03,
+                "logicalCycleTime": "(4 * twoQubitGateTime + 2 * oneQubitMeasurementTime) * codeDistance",
+                "physicalQubitsPerLogicalQubit": "2 * codeDistance * codeDistance",
+                "maxCodeDistance": 50
+            },
+            "errorBudget": 0.001,
+            "qubitParams": {
+                "instructionSet": "GateBased",
+                "name": "qubit_gate_ns_e3",
+                "oneQubitMeasurementTime": "100 ns",
+                "oneQubitGateTime": "50 ns",
+                "twoQubitGateTime": "50 ns",
+                "tGateTime": "50 ns",
+                "oneQubitMeasurementErrorRate": 0.001,
+                "oneQubitGateErrorRate": 0.001,
+                "twoQubitGateErrorRate": 0.001,
+                "tGateErrorRate": 0.001,
+                "idleErrorRate": 0.001
+            },
+            "constraints": { "maxDistillationRounds": 3 },
+            "estimateType": "singlePoint"
+        },
+        "physicalCounts": {
+            "physicalQubits": 12000,
+            "runtime": 400000,
+            "rqops": 40000000,
+            "breakdown": {
+                "algorithmicLogicalQubits": 10,
+                "algorithmicLogicalDepth": 100,
+                "logicalDepth": 100,
+                "numTstates": 20,
+                "clockFrequency": 250000.0,
+                "numTfactories": 1,
+                "numTfactoryRuns": 20,
+                "physicalQubitsForTfactories": 7000,
+                "physicalQubitsForAlgorithm": 5000,
+                "requiredLogicalQubitErrorRate": 1e-7,
+                "requiredLogicalTstateErrorRate": 1e-5,
+                "numTsPerRotation": null,
+                "cliffordErrorRate": 0.001
+            }
+        },
+        "physicalCountsFormatted": {
+            "runtime": "400us",
+            "rqops": "40.00M",
+            "physicalQubits": "12.00k",
+            "algorithmicLogicalQubits": "10",
+            "algorithmicLogicalDepth": "100",
+            "logicalDepth": "100",
+            "numTstates": "20",
+            "numTfactories": "1",
+            "numTfactoryRuns": "20",
+            "physicalQubitsForAlgorithm": "5.00k",
+            "physicalQubitsForTfactories": "7.00k",
+            "physicalQubitsForTfactoriesPercentage": "58.33 %",
+            "requiredLogicalQubitErrorRate": "1.00e-7",
+            "requiredLogicalTstateErrorRate": "1.00e-5",
+            "physicalQubitsPerLogicalQubit": "500",
+            "logicalCycleTime": "4us",
+            "clockFrequency": "250.00k",
+            "logicalErrorRate": "3.00e-8"
+        },
+        "logicalQubit": {
+            "codeDistance": 15,
+            "physicalQubits": 500,
+            "logicalCycleTime": 4000,
+            "logicalErrorRate": 3e-8
+        },
+        "errorBudget": { "logical": 0.0005, "tstates": 0.0005, "rotations": 0.0 },
+        "logicalCounts": {
+            "numQubits": 4,
+            "tCount": 20,
+            "rotationCount": 0,
+            "rotationDepth": 0,
+            "cczCount": 0,
+            "ccixCount": 0,
+            "measurementCount": 4
+        },
+        "reportData": { "groups": [], "assumptions": [] }
+    }))
+    .expect("the sample deserializes as an estimate")
+}
@@ -0,0 +1,191 @@
+use std::collections::{HashMap, HashSet};
+use std::{fs, io};
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+use qsc_frontend::compile::{SourceContents, SourceName};
+use qsc_project::{FileSystem, PackageGraphSources, Project, StdFs};
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{Error, Result};
+
+pub(crate) const MANIFEST_FILE: &str = "qsharp.json";
+
+/**
+    A Q# program to estimate: its sources and, optionally, an entry expression such as
+    `Sample.QFT(64)` to run instead of the `@EntryPoint()` operation. With an entry
+    expression, library operations can be estimated with concrete arguments without
+    editing the source.
+**/
+pub struct Program {
+    pub name: Arc<str>,
+    pub(crate) package_graph_sources: PackageGraphSources,
+    pub entry: Option<String>,
+}
+
+impl Program {
+    /// Loads a Q# project from a directory with a `qsharp.json` manifest (or the manifest
+    /// itself), otherwise reads a single Q# file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            Self::from_project_dir(path)
+        } else if path.file_name().is_some_and(|name| name == MANIFEST_FILE) {
+            // The parent of a bare `qsharp.json` is the empty path, not `None`.
+            let directory = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+            Self::from_project_dir(directory.unwrap_or(Path::new(".")))
+        } else {
+            Self::from_file(path)
+        }
+    }
+
+    /// Reads a single Q# file, or standard input for `-`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let (source_name, source_contents) = read_source(path)?;
+        Ok(Self::from_source(source_name, source_contents))
+    }
+
+    pub fn from_source(source_name: SourceName, source_contents: SourceContents) -> Self {
+        let project = Project::from_single_file(
+            Arc::from(source_name.as_ref()),
+            source_contents
+        );
+
+        Program {
+            name: source_name,
+            package_graph_sources: project.package_graph_sources,
+            entry: None,
+        }
+    }
+
+    /// Loads every source of the project in `directory` and of its local path dependencies,
+    /// as listed by their `qsharp.json` manifests.
+    pub fn from_project_dir(directory: impl AsRef<Path>) -> Result<Self> {
+        let directory = fs::canonicalize(directory.as_ref()).map_err(|e| Error::read(directory.as_ref().display(), e))?;
+
+        let project = StdFs
+            .load_project(&directory, None)
+            .map_err(|errors| project_error(&directory, errors))?;
+        if !project.errors.is_empty() {
+            return Err(project_error(&directory, project.errors));
+        }
+
+        Ok(Program {
+            name: project.name,
+            package_graph_sources: project.package_graph_sources,
+            entry: None,
+        })
+    }
+
+    /// Fetches a program over HTTP: a single Q# file, or a project when the URL names a
+    /// `qsharp.json` manifest. There is no directory listing over HTTP, so project manifests
+    /// (including those of path dependencies) must list their `files`.
+    pub async fn fetch(url: &str) -> Result<Self> {
+        let manifest_url = Url::parse(url).map_err(|e| Error::read(url, e))?;
+        if !manifest_url.path().ends_with(&format!("/{MANIFEST_FILE}")) {
+            let contents = crate::fetch_qs_file(url).await?;
+            return Ok(Self::from_source(url.into(), contents.into()));
+        }
+
+        // Mirror the project and its dependencies into a temporary directory, keeping their
+        // relative layout, and load it like a local project.
+        let mirror = tempfile::tempdir().map_err(|e| Error::read(url, e))?;
+        let mut pending = vec![manifest_url.clone()];
+        let mut fetched = HashSet::new();
+        while let Some(manifest_url) = pending.pop() {
+            if !fetched.insert(manifest_url.clone()) {
+                continue;
+            }
+            let contents = crate::fetch_qs_file(manifest_url.as_str()).await?;
+            let manifest: Manifest = serde_json::from_str(&contents).map_err(|e| Error::read(&manifest_url, e))?;
+            let files = manifest
+                .files
+                .ok_or_else(|| Error::read(&manifest_url, "the manifest must list its `files` to be fetched"))?;
+
+            write_mirrored(mirror.path(), &manifest_url, &contents)?;
+            for file in files {
+                let file_url = join_url(&manifest_url, &file)?;
+                let contents = crate::fetch_qs_file(file_url.as_str()).await?;
+                write_mirrored(mirror.path(), &file_url, &contents)?;
+            }
+            for dependency in manifest.dependencies.into_values() {
+                if let Some(path) = dependency.path {
+                    pending.push(join_url(&manifest_url, &format!("{}/{MANIFEST_FILE}", path.trim_end_matches('/')))?);
+                }
+            }
+        }
+
+        let root = mirror.path().join(mirrored_path(&manifest_url));
+        let mut program = Self::from_project_dir(root.parent().expect("a manifest path has a parent"))?;
+        program.name = url.into();
+        Ok(program)
+    }
+
+    /// Sets the entry expression; a blank one means the `@EntryPoint()` operation.
+    pub fn with_entry(mut self, entry: Option<String>) -> Self {
+        self.entry = entry.filter(|entry| !entry.trim().is_empty());
+        self
+    }
+}
+
+pub(crate) fn read_source(path: impl AsRef<Path>) -> Result<(SourceName, SourceContents)> {
+    let path = path.as_ref();
+    if path.as_os_str() == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| Error::read("<stdin>", e))?;
+
+        Ok(("<stdin>".into(), input.into()))
+    } else {
+        let contents = fs::read_to_string(path).map_err(|e| Error::read(path.display(), e))?;
+
+        Ok((path.to_string_lossy().into(), contents.into()))
+    }
+}
+
+/**
+    The parts of a `qsharp.json` manifest needed to fetch a project; the rest is read by
+    `qsc_project` once the project is on disk.
+**/
+#[derive(Deserialize)]
+struct Manifest {
+    files: Option<Vec<String>>,
+    #[serde(default)]
+    dependencies: HashMap<String, Dependency>,
+}
+
+#[derive(Deserialize)]
+struct Dependency {
+    path: Option<String>,
+}
+
+fn join_url(base: &Url, path: &str) -> Result<Url> {
+    base.join(path)
+        .map_err(|e| Error::read(base, format!("invalid path `{path}`: {e}")))
+}
+
+/**
+    Where a fetched file goes inside the mirror directory: its URL path, which `Url` has
+    already normalized, so `..` segments cannot escape the mirror.
+**/
+fn mirrored_path(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    format!("{}{}", host, url.path())
+}
+
+fn write_mirrored(mirror: &Path, url: &Url, contents: &str) -> Result<()> {
+    let path = mirror.join(mirrored_path(url));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::read(url, e))?;
+    }
+    fs::write(&path, contents).map_err(|e| Error::read(url, e))
+}
+
+fn project_error(directory: &Path, errors: Vec<qsc_project::Error>) -> Error {
+    Error::Project {
+        path: directory.display().to_string(),
+        errors,
+    }
+}
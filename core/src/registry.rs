@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::{load_toml_or_json, EstimationConfig, Error, Params, Result, TargetProfile};
+
+/// Environment variable with the path of the registry to use when none is given explicitly.
+pub const REGISTRY_ENV: &str = "QRE_PROFILE_REGISTRY";
+
+/**
+    Named hardware profiles a team shares, read from a TOML (`.toml`) or JSON file:
+
+    ```toml
+    [profiles.ourlab-2027]
+    description = "Our 2027 transmon roadmap"
+    qubitParams = { name = "ourlab_qubit", instructionSet = "GateBased", ... }
+    qecScheme = { name = "surface_code" }
+    constraints = { maxTFactories = 10 }
+    ```
+
+    Each profile takes the same fields as estimation params. Every profile is validated
+    when the file is loaded, so a broken entry is reported before anything is estimated.
+**/
+#[derive(Deserialize, Clone)]
+pub struct Registry {
+    pub profiles: BTreeMap<String, HardwareProfile>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HardwareProfile {
+    #[serde(default)]
+    pub description: String,
+    #[serde(flatten)]
+    pub params: Params,
+}
+
+impl Registry {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut registry: Registry = load_toml_or_json(path, |message| Error::registry(path, message))?;
+        for (name, profile) in &registry.profiles {
+            profile
+                .params
+                .validate()
+                .map_err(|report| Error::registry(path, format!("profile `{name}`: {}", crate::error::messages(&report))))?;
+        }
+
+        registry.path = path.to_path_buf();
+        Ok(registry)
+    }
+
+    /// Loads the registry named by `QRE_PROFILE_REGISTRY`, if it is set.
+    pub fn from_env() -> Result<Option<Self>> {
+        std::env::var_os(REGISTRY_ENV).map(Self::load).transpose()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, name: &str) -> Result<&HardwareProfile> {
+        self.profiles.get(name).ok_or_else(|| {
+            let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            Error::Config {
+                label: name.to_string(),
+                message: format!(
+                    "no such hardware profile in `{}` (available: {})",
+                    self.path.display(),
+                    names.join(", ")
+                ),
+            }
+        })
+    }
+
+    /// The configuration for estimating on the profile `name`, labeled with its name.
+    pub fn estimation_config(&self, name: &str) -> Result<EstimationConfig> {
+        self.resolve(EstimationConfig {
+            label: name.to_string(),
+            detail: String::new(),
+            profile: TargetProfile::default(),
+            hardware: Some(name.to_string()),
+            params: crate::default_estimation_config().params,
+        })
+    }
+
+    /// Replaces the params of a config naming a hardware profile with the profile's, and
+    /// an empty detail with its description. Other configs are returned as they are.
+    pub fn resolve(&self, mut config: EstimationConfig) -> Result<EstimationConfig> {
+        if let Some(name) = config.hardware.take() {
+            let profile = self.get(&name)?;
+            config.params = profile.params.clone();
+            if config.detail.is_empty() {
+                config.detail = profile.description.clone();
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Registry;
+    use crate::{default_estimation_config, EstimationConfig};
+
+    fn registry() -> Registry {
+        serde_json::from_value(serde_json::json!({
+            "profiles": {
+                "ourlab-2027": {
+                    "description": "Our 2027 transmon roadmap",
+                    "qubitParams": { "name": "qubit_gate_ns_e4" },
+                    "qecScheme": { "name": "surface_code" },
+                    "errorBudget": 1e-4
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn resolves_a_named_hardware_profile() {
+        let config = EstimationConfig {
+            label: "roadmap".to_string(),
+            detail: String::new(),
+            hardware: Some("ourlab-2027".to_string()),
+            ..default_estimation_config()
+        };
+
+        let resolved = registry().resolve(config).unwrap();
+        assert_eq!(resolved.label, "roadmap");
+        assert_eq!(resolved.detail, "Our 2027 transmon roadmap");
+        assert_eq!(resolved.hardware, None);
+        assert_eq!(resolved.params.qubit_params.name, "qubit_gate_ns_e4");
+        assert!(resolved.params.error_budget.is_some());
+    }
+
+    #[test]
+    fn leaves_configs_without_a_hardware_profile_alone() {
+        let resolved = registry().resolve(default_estimation_config()).unwrap();
+        assert_eq!(resolved.params.qubit_params.name, "qubit_maj_ns_e6");
+    }
+
+    #[test]
+    fn reports_unknown_hardware_profiles_with_the_available_ones() {
+        let config = EstimationConfig {
+            hardware: Some("ourlab-2030".to_string()),
+            ..default_estimation_config()
+        };
+        let error = registry().resolve(config).err().unwrap().to_string();
+        assert!(error.contains("ourlab-2027"), "{error}");
+    }
+}
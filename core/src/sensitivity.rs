@@ -0,0 +1,247 @@
+use serde_json::Value;
+
+use crate::{estimate_logical_counts, Error, EstimationConfig, EstimationOutput, Result};
+
+/// Relative change each parameter is perturbed by when no other is given.
+pub const DEFAULT_STEP: f64 = 0.1;
+
+/// Numeric qubit parameters, by their JSON names. Times are perturbed in their own unit.
+const QUBIT_PARAMETERS: &[&str] = &[
+    "oneQubitMeasurementTime",
+    "oneQubitGateTime",
+    "twoQubitGateTime",
+    "twoQubitJointMeasurementTime",
+    "tGateTime",
+    "oneQubitMeasurementErrorRate",
+    "oneQubitGateErrorRate",
+    "twoQubitGateErrorRate",
+    "twoQubitJointMeasurementErrorRate",
+    "tGateErrorRate",
+    "idleErrorRate",
+];
+
+/// Numeric QEC scheme parameters; the formulas are left alone.
+const QEC_PARAMETERS: &[&str] = &["errorCorrectionThreshold", "crossingPrefactor"];
+
+/**
+    How strongly each hardware parameter drives an estimate, strongest first.
+**/
+pub struct Sensitivity {
+    pub baseline: EstimationOutput,
+    pub step: f64,
+    pub elasticities: Vec<Elasticity>,
+    /// Parameters whose perturbed estimates failed, e.g. an error rate pushed above the
+    /// QEC threshold, with the estimator's message.
+    pub failures: Vec<(String, String)>,
+}
+
+/**
+    The elasticity of physical qubits and runtime to one parameter: the relative change
+    of the result per relative change of the parameter, from estimates at `1 - step` and
+    `1 + step` times its value. An elasticity of 2 means 1% more of the parameter costs
+    about 2% more; code distances are whole numbers, so small steps often show none.
+**/
+#[derive(Clone, Debug)]
+pub struct Elasticity {
+    /// E.g. `qubitParams.tGateErrorRate`.
+    pub parameter: String,
+    /// The parameter's value in the baseline, e.g. `50 ns` or `0.001`.
+    pub value: String,
+    pub physical_qubits: f64,
+    pub runtime: f64,
+}
+
+impl Elasticity {
+    /// What the ranking sorts by: the larger of the two elasticities in magnitude.
+    pub fn magnitude(&self) -> f64 {
+        self.physical_qubits.abs().max(self.runtime.abs())
+    }
+}
+
+/**
+    Perturbs every numeric qubit and QEC parameter of `config` around the value the
+    estimator used in `baseline`, so presets are analyzed as well as custom models, and
+    re-estimates from the baseline's logical counts.
+**/
+pub fn analyze(baseline: EstimationOutput, config: &EstimationConfig, step: f64) -> Result<Sensitivity> {
+    let counts = baseline.logical_counts.clone();
+    analyze_with(baseline, config, step, |perturbed| estimate_logical_counts(&counts, perturbed))
+}
+
+/// `analyze` with the perturbed configurations estimated by `estimate`.
+fn analyze_with(
+    baseline: EstimationOutput,
+    config: &EstimationConfig,
+    step: f64,
+    estimate: impl Fn(&EstimationConfig) -> Result<EstimationOutput>,
+) -> Result<Sensitivity> {
+    if !(step > 0.0 && step < 1.0) {
+        return Err(Error::Config {
+            label: config.label.clone(),
+            message: format!("sensitivity step must be between 0 and 1, got {step}"),
+        });
+    }
+
+    let resolved = serde_json::to_value(&baseline.job_params).expect("job params serialize to JSON");
+    let mut elasticities = Vec::new();
+    let mut failures = Vec::new();
+    for (group, parameters) in [("qubitParams", QUBIT_PARAMETERS), ("qecScheme", QEC_PARAMETERS)] {
+        for &parameter in parameters {
+            // Parameters the instruction set does not use are null.
+            let Some(value) = resolved[group].get(parameter).filter(|value| !value.is_null()) else {
+                continue;
+            };
+            let name = format!("{group}.{parameter}");
+
+            let perturbed = |factor: f64| -> Result<EstimationOutput> {
+                let scaled = scale(value, factor).ok_or_else(|| Error::Config {
+                    label: config.label.clone(),
+                    message: format!("{name} is not a number: {value}"),
+                })?;
+                estimate(&perturb(config, group, parameter, scaled)?)
+            };
+            match (perturbed(1.0 - step), perturbed(1.0 + step)) {
+                (Ok(lower), Ok(upper)) => elasticities.push(Elasticity {
+                    value: describe(value),
+                    physical_qubits: elasticity(
+                        baseline.physical_counts.physical_qubits,
+                        lower.physical_counts.physical_qubits,
+                        upper.physical_counts.physical_qubits,
+                        step,
+                    ),
+                    runtime: elasticity(
+                        baseline.physical_counts.runtime,
+                        lower.physical_counts.runtime,
+                        upper.physical_counts.runtime,
+                        step,
+                    ),
+                    parameter: name,
+                }),
+                (Err(error), _) | (_, Err(error)) => failures.push((name, error.to_string())),
+            }
+        }
+    }
+    elasticities.sort_by(|a, b| b.magnitude().total_cmp(&a.magnitude()));
+
+    Ok(Sensitivity {
+        baseline,
+        step,
+        elasticities,
+        failures,
+    })
+}
+
+/// `config` with one parameter overridden, on top of the preset if it names one.
+fn perturb(config: &EstimationConfig, group: &str, parameter: &str, value: Value) -> Result<EstimationConfig> {
+    let mut params = serde_json::to_value(&config.params).expect("params serialize to JSON");
+    params[group][parameter] = value;
+
+    Ok(EstimationConfig {
+        params: serde_json::from_value(params).map_err(|e| Error::Config {
+            label: config.label.clone(),
+            message: e.to_string(),
+        })?,
+        ..config.clone()
+    })
+}
+
+/// Scales a number, a time like `50 ns`, or a measurement error rate's process and readout parts.
+fn scale(value: &Value, factor: f64) -> Option<Value> {
+    match value {
+        Value::Number(number) => Some(Value::from(number.as_f64()? * factor)),
+        Value::String(time) => {
+            let (amount, unit) = time.split_once(' ')?;
+            Some(Value::from(format!("{} {unit}", amount.parse::<f64>().ok()? * factor)))
+        }
+        Value::Object(parts) => parts
+            .iter()
+            .map(|(key, part)| Some((key.clone(), scale(part, factor)?)))
+            .collect::<Option<serde_json::Map<_, _>>>()
+            .map(Value::Object),
+        _ => None,
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Object(parts) => {
+            let parts: Vec<String> = parts.iter().map(|(key, part)| format!("{key} {part}")).collect();
+            parts.join(", ")
+        }
+        _ => value.to_string(),
+    }
+}
+
+fn elasticity(baseline: u64, lower: u64, upper: u64, step: f64) -> f64 {
+    (upper as f64 - lower as f64) / (2.0 * step * baseline as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze_with, elasticity};
+    use crate::{default_estimation_config, output, Error, EstimationConfig, QubitParams};
+
+    #[test]
+    fn computes_the_central_difference() {
+        assert_eq!(elasticity(1000, 900, 1100, 0.1), 1.0);
+        assert_eq!(elasticity(1000, 1000, 1000, 0.1), 0.0);
+        assert_eq!(elasticity(1000, 1100, 900, 0.05), -2.0);
+    }
+
+    #[test]
+    fn ranks_parameters_by_how_much_the_estimate_changes() {
+        let mut config = EstimationConfig {
+            label: "sample".to_string(),
+            ..default_estimation_config()
+        };
+        config.params.qubit_params = QubitParams::preset("qubit_gate_ns_e3");
+
+        // Physical qubits grow in proportion to the T gate error rate, runtime with the
+        // square of the T gate time; the idle error rate cannot be estimated.
+        let sensitivity = analyze_with(output::sample(), &config, 0.1, |perturbed| {
+            let qubit = &perturbed.params.qubit_params;
+            let mut output = output::sample();
+            if let Some(rate) = qubit.t_gate_error_rate {
+                output.physical_counts.physical_qubits = (12000.0 * rate / 0.001).round() as u64;
+            }
+            if let Some(time) = &qubit.t_gate_time {
+                let ns: f64 = time.strip_suffix(" ns").unwrap().parse().unwrap();
+                output.physical_counts.runtime = (400000.0 * (ns / 50.0).powi(2)).round() as u64;
+            }
+            match qubit.idle_error_rate {
+                Some(_) => Err(Error::Estimation {
+                    label: perturbed.label.clone(),
+                    message: "too noisy".to_string(),
+                    constraints: None,
+                }),
+                None => Ok(output),
+            }
+        })
+        .unwrap();
+
+        let first = &sensitivity.elasticities[0];
+        assert_eq!((first.parameter.as_str(), first.value.as_str()), ("qubitParams.tGateTime", "50 ns"));
+        assert!((first.runtime - 2.0).abs() < 1e-9, "{}", first.runtime);
+        assert_eq!(first.physical_qubits, 0.0);
+
+        let second = &sensitivity.elasticities[1];
+        assert_eq!(second.parameter, "qubitParams.tGateErrorRate");
+        assert!((second.physical_qubits - 1.0).abs() < 1e-9, "{}", second.physical_qubits);
+        assert!(sensitivity.elasticities[2..].iter().all(|elasticity| elasticity.magnitude() == 0.0));
+
+        // Parameters the baseline has no value for, like the joint measurement time of
+        // gate-based qubits, are left out.
+        assert_eq!(sensitivity.elasticities.len(), 10);
+        assert_eq!(sensitivity.failures.len(), 1);
+        assert_eq!(sensitivity.failures[0].0, "qubitParams.idleErrorRate");
+    }
+
+    #[test]
+    fn rejects_steps_outside_zero_and_one() {
+        for step in [0.0, 1.0, -0.1, f64::NAN] {
+            let result = analyze_with(output::sample(), &default_estimation_config(), step, |_| unreachable!());
+            assert!(matches!(result, Err(Error::Config { .. })), "{step}");
+        }
+    }
+}
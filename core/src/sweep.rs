@@ -0,0 +1,301 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    default_estimation_config, load_toml_or_json, Constraints, ErrorBudget, EstimationConfig, EstimationOutput, Error, Params,
+    QecScheme, QubitParams, Result, TargetProfile,
+};
+
+/**
+    A parameter sweep: every combination of the listed error budgets, qubit models, QEC
+    schemes and constraints, estimated for one program. Read from a TOML (`.toml`) or JSON
+    file:
+
+    ```json
+    {
+        "qubitParams": [{ "name": "qubit_gate_ns_e3" }, { "name": "qubit_gate_us_e4" }],
+        "qecSchemes": [{ "name": "surface_code" }],
+        "errorBudgets": [1e-3, 1e-4],
+        "constraints": [{}, { "maxTFactories": 4 }]
+    }
+    ```
+
+    An empty list leaves that parameter at its default: the default qubit model and QEC
+    scheme, the estimator's own error budget and no constraints.
+**/
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepSpec {
+    #[serde(default)]
+    pub profile: TargetProfile,
+    #[serde(default)]
+    pub qubit_params: Vec<QubitParams>,
+    #[serde(default)]
+    pub qec_schemes: Vec<QecScheme>,
+    #[serde(default)]
+    pub error_budgets: Vec<ErrorBudget>,
+    #[serde(default)]
+    pub constraints: Vec<Constraints>,
+    /// Threads to estimate on; defaults to the available parallelism.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+}
+
+/**
+    One estimated point of a sweep. `index` is the point's position in `SweepSpec::configs`;
+    points arrive in the order they finish.
+**/
+pub struct SweepPoint {
+    pub index: usize,
+    pub config: EstimationConfig,
+    pub output: Result<EstimationOutput>,
+}
+
+impl SweepSpec {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        load_toml_or_json(path, |message| Error::Config {
+            label: path.display().to_string(),
+            message,
+        })
+    }
+
+    /// The configuration of every point, varying the constraints fastest and the qubit
+    /// model slowest. Labels name the values of each swept parameter, e.g.
+    /// "qubit_gate_ns_e3, surface_code, errorBudget = 0.001".
+    pub fn configs(&self) -> Vec<EstimationConfig> {
+        let default = default_estimation_config().params;
+        let qubit_params = axis(&self.qubit_params, default.qubit_params);
+        let qec_schemes = axis(&self.qec_schemes, default.qec_scheme);
+        let error_budgets = optional_axis(&self.error_budgets);
+        let constraints = optional_axis(&self.constraints);
+
+        let mut configs = Vec::new();
+        for qubit in &qubit_params {
+            for qec in &qec_schemes {
+                for error_budget in &error_budgets {
+                    for constraints in &constraints {
+                        let mut label = vec![qubit.name.clone(), qec.name.clone()];
+                        if let Some(error_budget) = error_budget {
+                            label.push(format!("errorBudget = {}", describe_error_budget(error_budget)));
+                        }
+                        if let Some(constraints) = constraints {
+                            label.push(format!("constraints: {constraints}"));
+                        }
+
+                        configs.push(EstimationConfig {
+                            label: label.join(", "),
+                            detail: String::new(),
+                            profile: self.profile,
+                            hardware: None,
+                            params: Params {
+                                qubit_params: qubit.clone(),
+                                qec_scheme: qec.clone(),
+                                error_budget: *error_budget,
+                                constraints: constraints.clone(),
+                                distillation_unit_specifications: None,
+                            },
+                        });
+                    }
+                }
+            }
+        }
+        configs
+    }
+
+    /// The number of points, from the lengths of the swept lists so the configurations are
+    /// not built; `None` if it overflows.
+    pub fn size(&self) -> Option<usize> {
+        [self.qubit_params.len(), self.qec_schemes.len(), self.error_budgets.len(), self.constraints.len()]
+            .into_iter()
+            .try_fold(1usize, |size, values| size.checked_mul(values.max(1)))
+    }
+
+    fn threads(&self) -> usize {
+        self.threads
+            .or_else(|| thread::available_parallelism().ok().map(usize::from))
+            .unwrap_or(1)
+            .max(1)
+    }
+}
+
+/**
+    Estimates `configs` with `estimate`, e.g. from the program's logical counts, on the
+    spec's threads, handing each point to `on_point` as soon as it is done.
+**/
+pub(crate) fn run(
+    spec: &SweepSpec,
+    configs: Vec<(usize, EstimationConfig)>,
+    estimate: impl Fn(&EstimationConfig) -> Result<EstimationOutput> + Sync,
+    mut on_point: impl FnMut(SweepPoint),
+) {
+    // Threads take the next unclaimed point rather than a fixed share, since points with
+    // large code distances take much longer than others.
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..spec.threads().min(configs.len()) {
+            let sender = sender.clone();
+            let (next, configs, estimate) = (&next, &configs, &estimate);
+            scope.spawn(move || {
+                while let Some((index, config)) = configs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let output = estimate(config);
+                    let point = SweepPoint {
+                        index: *index,
+                        config: config.clone(),
+                        output,
+                    };
+                    if sender.send(point).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for point in receiver {
+            on_point(point);
+        }
+    });
+}
+
+/// The swept values, or the default alone when none are given.
+fn axis<T: Clone>(values: &[T], default: T) -> Vec<T> {
+    if values.is_empty() {
+        vec![default]
+    } else {
+        values.to_vec()
+    }
+}
+
+/// The swept values, or leaving the parameter unset when none are given.
+fn optional_axis<T: Clone>(values: &[T]) -> Vec<Option<T>> {
+    if values.is_empty() {
+        vec![None]
+    } else {
+        values.iter().cloned().map(Some).collect()
+    }
+}
+
+fn describe_error_budget(error_budget: &ErrorBudget) -> String {
+    match error_budget {
+        ErrorBudget::Total(total) => total.to_string(),
+        ErrorBudget::Partitioned { logical, t_states, rotations } => {
+            format!("{logical}/{t_states}/{rotations}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, SweepSpec};
+    use crate::{output, Constraints, Error, ErrorBudget, QecScheme, QubitParams};
+
+    fn spec() -> SweepSpec {
+        SweepSpec {
+            qubit_params: vec![QubitParams::preset("qubit_gate_ns_e3"), QubitParams::preset("qubit_gate_us_e4")],
+            error_budgets: vec![ErrorBudget::Total(1e-3), ErrorBudget::Total(1e-4)],
+            ..SweepSpec::default()
+        }
+    }
+
+    #[test]
+    fn varies_the_later_parameters_fastest() {
+        let labels: Vec<String> = spec().configs().into_iter().map(|config| config.label).collect();
+        assert_eq!(
+            labels,
+            [
+                "qubit_gate_ns_e3, surface_code, errorBudget = 0.001",
+                "qubit_gate_ns_e3, surface_code, errorBudget = 0.0001",
+                "qubit_gate_us_e4, surface_code, errorBudget = 0.001",
+                "qubit_gate_us_e4, surface_code, errorBudget = 0.0001",
+            ]
+        );
+    }
+
+    #[test]
+    fn labels_only_the_swept_parameters() {
+        let spec = SweepSpec {
+            qec_schemes: vec![QecScheme::preset("floquet_code")],
+            error_budgets: vec![ErrorBudget::Partitioned {
+                logical: 1e-4,
+                t_states: 2e-4,
+                rotations: 0.0,
+            }],
+            constraints: vec![Constraints {
+                max_t_factories: Some(4),
+                ..Constraints::default()
+            }],
+            ..SweepSpec::default()
+        };
+
+        let configs = spec.configs();
+        assert_eq!(configs.len(), 1);
+        assert!(configs[0].label.starts_with("qubit_maj_ns_e6, floquet_code, errorBudget = 0.0001/0.0002/0, constraints: "));
+        assert_eq!(configs[0].params.constraints.as_ref().unwrap().max_t_factories, Some(4));
+    }
+
+    #[test]
+    fn counts_points_without_building_them() {
+        assert_eq!(spec().size(), Some(4));
+        assert_eq!(SweepSpec::default().size(), Some(1));
+
+        let huge = SweepSpec {
+            qubit_params: vec![QubitParams::default(); 1 << 16],
+            qec_schemes: vec![QecScheme::default(); 1 << 16],
+            error_budgets: vec![ErrorBudget::Total(1e-3); 1 << 16],
+            constraints: vec![Constraints::default(); 1 << 16],
+            ..SweepSpec::default()
+        };
+        assert_eq!(huge.size(), None);
+    }
+
+    #[test]
+    fn hands_over_every_point_with_its_own_result() {
+        let spec = SweepSpec { threads: Some(3), ..spec() };
+        let configs: Vec<_> = spec.configs().into_iter().enumerate().collect();
+
+        let mut points = Vec::new();
+        run(
+            &spec,
+            configs.clone(),
+            |config| {
+                if config.label.contains("us_e4") {
+                    Err(Error::Estimation {
+                        label: config.label.clone(),
+                        message: "failed".to_string(),
+                        constraints: None,
+                    })
+                } else {
+                    Ok(output::sample())
+                }
+            },
+            |point| points.push(point),
+        );
+
+        points.sort_by_key(|point| point.index);
+        assert_eq!(points.len(), configs.len());
+        for (point, (index, config)) in points.iter().zip(&configs) {
+            assert_eq!(point.index, *index);
+            assert_eq!(point.config.label, config.label);
+            match &point.output {
+                Ok(_) => assert!(config.label.contains("ns_e3")),
+                Err(Error::Estimation { label, .. }) => assert_eq!(label, &config.label),
+                Err(error) => panic!("unexpected error {error}"),
+            }
+        }
+    }
+
+    #[test]
+    fn estimates_in_order_on_one_thread() {
+        let spec = SweepSpec { threads: Some(1), ..spec() };
+        let configs: Vec<_> = spec.configs().into_iter().enumerate().skip(1).collect();
+
+        let mut indices = Vec::new();
+        run(&spec, configs, |_| Ok(output::sample()), |point| indices.push(point.index));
+        assert_eq!(indices, [1, 2, 3]);
+    }
+}
@@ -5,7 +5,7 @@ use juniper::http::{graphiql::graphiql_source, GraphQLRequest};
 use std::{sync::Arc};
 use actix_cors::Cors;
 use actix_web::web::Data;
-use crate::schema::{create_schema, Schema};
+use crate::schema::{create_schema, Context, Schema};
 
 #[get("/")]
 async fn hello() -> impl Responder {
@@ -27,8 +27,8 @@ async fn graphql_playground() -> impl Responder {
 }
 
 #[route("/graphql", method = "GET", method = "POST")]
-async fn graphql(st: web::Data<Schema>, data: web::Json<GraphQLRequest>) -> impl Responder {
-    let user = data.execute(&st, &()).await;
+async fn graphql(st: web::Data<Schema>, context: web::Data<Context>, data: web::Json<GraphQLRequest>) -> impl Responder {
+    let user = data.execute(&st, &context).await;
     HttpResponse::Ok().json(user)
 }
 
@@ -36,11 +36,25 @@ async fn graphql(st: web::Data<Schema>, data: web::Json<GraphQLRequest>) -> impl
 async fn main() -> std::io::Result<()> {
     let schema = Arc::new(create_schema());
 
+    // A broken registry or cache setting is reported now rather than on the first request.
+    let (registry, cache) = match qcore::Registry::from_env().and_then(|registry| Ok((registry, qcore::ResultCache::from_env()?))) {
+        Ok(state) => state,
+        Err(error) => {
+            eprintln!("{:?}", miette::Report::new(error));
+            std::process::exit(1);
+        }
+    };
+    if let Some(registry) = &registry {
+        println!("Loaded {} hardware profiles from {:?}", registry.profiles.len(), registry.path());
+    }
+    let context = Arc::new(Context { registry, cache: Arc::new(cache) });
+
     println!("GraphiQL playground: http://localhost:8080/graphiql");
 
     HttpServer::new(move || {
         App::new()
             .app_data(Data::from(schema.clone()))
+            .app_data(Data::from(context.clone()))
             .service(graphql_playground)
             .service(graphql)
             .wrap(Cors::permissive())
@@ -1,23 +1,125 @@
-use juniper::{EmptySubscription, FieldResult, RootNode};
-use juniper::{GraphQLInputObject, GraphQLObject};
+use std::sync::Arc;
+use juniper::{EmptySubscription, FieldError, FieldResult, Object, RootNode, Value};
+use juniper::{GraphQLEnum, GraphQLInputObject, GraphQLObject};
+use qcore::{output, EstimationConfig, EstimationOutput, FrontierOutput, Program};
 
 #[derive(GraphQLObject)]
 struct EstimationResult {
     file: String,
+    label: String,
     status: String,
     job_params: JobParams,
     physical_counts: PhysicalCounts,
     physical_counts_formatted: PhysicalCountsFormatted,
     logical_qubit: LogicalQubit,
+    tfactory: Option<TFactory>,
     error_budget: ErrorBudget,
     logical_counts: LogicalCounts,
     report_data: ReportData,
+    #[graphql(description = "Whether the result was served from the server's result cache")]
+    cache_hit: bool,
+    #[graphql(description = "The `// @budget` annotations of the estimated callable checked against this result; malformed ones fail")]
+    budget_checks: Vec<BudgetCheck>,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "One budget limit checked against an estimate, or a malformed budget annotation, which fails")]
+struct BudgetCheck {
+    #[graphql(description = "The count limited, e.g. \"physicalQubits\"; null for a malformed annotation")]
+    metric: Option<String>,
+    #[graphql(description = "The limit, in nanoseconds for the runtime; null for a malformed annotation")]
+    max: Option<f64>,
+    actual: Option<f64>,
+    passed: bool,
+    message: String,
+    location: Option<SourceLocation>,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "Where in the sources something is; lines and columns start at 1")]
+struct SourceLocation {
+    file: Option<String>,
+    line: i32,
+    column: i32,
+}
+
+/// Pareto-optimal trade-offs between physical qubits and runtime, by increasing runtime.
+#[derive(GraphQLObject)]
+struct FrontierResult {
+    file: String,
+    label: String,
+    status: String,
+    job_params: JobParams,
+    entries: Vec<FrontierEntry>,
+    logical_counts: LogicalCounts,
+    #[graphql(description = "The frontier points as CSV, for plotting")]
+    csv: String,
+}
+
+/// How strongly each qubit and QEC parameter drives an estimate, strongest first.
+#[derive(GraphQLObject)]
+struct SensitivityResult {
+    file: String,
+    label: String,
+    #[graphql(description = "Relative change each parameter was perturbed by, in both directions")]
+    step: f64,
+    baseline: EstimationResult,
+    elasticities: Vec<Elasticity>,
+    #[graphql(description = "Parameters whose perturbed estimates failed, e.g. an error rate above the QEC threshold")]
+    failures: Vec<SensitivityFailure>,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "Relative change of physical qubits and runtime per relative change of one parameter")]
+struct Elasticity {
+    #[graphql(description = "E.g. \"qubitParams.tGateErrorRate\"")]
+    parameter: String,
+    #[graphql(description = "The parameter's value in the baseline")]
+    value: String,
+    physical_qubits: f64,
+    runtime: f64,
+}
+
+#[derive(GraphQLObject)]
+struct SensitivityFailure {
+    parameter: String,
+    message: String,
+}
+
+/// One item of a batch estimation: its result, or why it could not be estimated.
+#[derive(GraphQLObject)]
+struct BatchItemResult {
+    label: String,
+    result: Option<EstimationResult>,
+    error: Option<String>,
+}
+
+/// One point of a sweep: its result, or why it could not be estimated.
+#[derive(GraphQLObject)]
+struct SweepPointResult {
+    #[graphql(description = "Position of the point in the sweep, varying constraints fastest and qubit models slowest")]
+    index: i32,
+    label: String,
+    result: Option<EstimationResult>,
+    error: Option<String>,
+}
+
+#[derive(GraphQLObject)]
+struct FrontierEntry {
+    logical_qubit: LogicalQubit,
+    tfactory: Option<TFactory>,
+    error_budget: ErrorBudget,
+    physical_counts: PhysicalCounts,
+    physical_counts_formatted: PhysicalCountsFormatted,
 }
 
 #[derive(GraphQLObject)]
 struct JobParams {
     qec_scheme: QecScheme,
+    #[graphql(description = "The total error budget requested")]
     error_budget: f64,
+    #[graphql(description = "The requested split of the error budget, if one was given instead of a total")]
+    error_budget_partition: Option<ErrorBudget>,
     qubit_params: QubitParams,
     constraints: Constraints,
     estimate_type: String,
@@ -30,7 +132,7 @@ struct QecScheme {
     crossing_prefactor: f64,
     logical_cycle_time: String,
     physical_qubits_per_logical_qubit: String,
-    max_code_distance: i32,
+    max_code_distance: f64,
 }
 
 #[derive(GraphQLObject)]
@@ -38,43 +140,55 @@ struct QubitParams {
     instruction_set: String,
     name: String,
     one_qubit_measurement_time: String,
-    one_qubit_gate_time: String,
-    two_qubit_gate_time: String,
+    one_qubit_gate_time: Option<String>,
+    two_qubit_gate_time: Option<String>,
+    two_qubit_joint_measurement_time: Option<String>,
     t_gate_time: String,
-    one_qubit_measurement_error_rate: f64,
-    one_qubit_gate_error_rate: f64,
-    two_qubit_gate_error_rate: f64,
+    one_qubit_measurement_error_rate: MeasurementErrorRate,
+    one_qubit_gate_error_rate: Option<f64>,
+    two_qubit_gate_error_rate: Option<f64>,
+    two_qubit_joint_measurement_error_rate: Option<MeasurementErrorRate>,
     t_gate_error_rate: f64,
     idle_error_rate: f64,
 }
 
+#[derive(GraphQLObject)]
+struct MeasurementErrorRate {
+    process: f64,
+    readout: f64,
+}
+
 #[derive(GraphQLObject)]
 struct Constraints {
-    max_distillation_rounds: i32,
+    max_distillation_rounds: f64,
+    logical_depth_factor: Option<f64>,
+    max_t_factories: Option<f64>,
+    max_duration: Option<String>,
+    max_physical_qubits: Option<f64>,
 }
 
 #[derive(GraphQLObject)]
 struct PhysicalCounts {
-    physical_qubits: i32,
-    runtime: i32,
-    rqops: i32,
+    physical_qubits: f64,
+    runtime: f64,
+    rqops: f64,
     breakdown: PhysicalCountsBreakdown,
 }
 
 #[derive(GraphQLObject)]
 struct PhysicalCountsBreakdown {
-    algorithmic_logical_qubits: i32,
-    algorithmic_logical_depth: i32,
-    logical_depth: i32,
-    num_tstates: i32,
+    algorithmic_logical_qubits: f64,
+    algorithmic_logical_depth: f64,
+    logical_depth: f64,
+    num_tstates: f64,
     clock_frequency: f64,
-    num_tfactories: i32,
-    num_tfactory_runs: i32,
-    physical_qubits_for_tfactories: i32,
-    physical_qubits_for_algorithm: i32,
+    num_tfactories: f64,
+    num_tfactory_runs: f64,
+    physical_qubits_for_tfactories: f64,
+    physical_qubits_for_algorithm: f64,
     required_logical_qubit_error_rate: f64,
     required_logical_tstate_error_rate: Option<f64>,
-    num_ts_per_rotation: Option<i32>,
+    num_ts_per_rotation: Option<f64>,
     clifford_error_rate: f64,
 }
 
@@ -103,9 +217,24 @@ struct PhysicalCountsFormatted {
 
 #[derive(GraphQLObject)]
 struct LogicalQubit {
-    code_distance: i32,
-    physical_qubits: i32,
-    logical_cycle_time: i32,
+    code_distance: f64,
+    physical_qubits: f64,
+    logical_cycle_time: f64,
+    logical_error_rate: f64,
+}
+
+#[derive(GraphQLObject)]
+struct TFactory {
+    physical_qubits: f64,
+    runtime: f64,
+    num_tstates: f64,
+    num_input_tstates: f64,
+    num_rounds: f64,
+    num_units_per_round: Vec<f64>,
+    unit_name_per_round: Vec<String>,
+    code_distance_per_round: Vec<f64>,
+    physical_qubits_per_round: Vec<f64>,
+    runtime_per_round: Vec<f64>,
     logical_error_rate: f64,
 }
 
@@ -118,13 +247,13 @@ struct ErrorBudget {
 
 #[derive(GraphQLObject)]
 struct LogicalCounts {
-    num_qubits: i32,
-    t_count: i32,
-    rotation_count: i32,
-    rotation_depth: i32,
-    ccz_count: i32,
-    ccix_count: i32,
-    measurement_count: i32,
+    num_qubits: f64,
+    t_count: f64,
+    rotation_count: f64,
+    rotation_depth: f64,
+    ccz_count: f64,
+    ccix_count: f64,
+    measurement_count: f64,
 }
 
 #[derive(GraphQLObject)]
@@ -148,116 +277,460 @@ struct ReportEntry {
     explanation: String,
 }
 
+#[derive(GraphQLObject)]
+#[graphql(description = "The estimator's built-in qubit models and QEC schemes")]
+struct Presets {
+    qubit_params: Vec<QubitPreset>,
+    qec_schemes: Vec<QecSchemePreset>,
+}
+
+#[derive(GraphQLObject)]
+struct QubitPreset {
+    description: String,
+    params: QubitParams,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A QEC scheme preset for the qubits of one instruction set")]
+struct QecSchemePreset {
+    description: String,
+    instruction_set: InstructionSet,
+    params: QecScheme,
+}
+
+/// GraphQL `Int` is 32 bits, too small for runtimes in nanoseconds or large qubit counts,
+/// so counts are exposed as `Float`, which holds every integer up to 2^53 exactly.
+fn to_float(value: u64) -> f64 {
+    value as f64
+}
+
+/// Source positions fit in an `Int`; anything beyond is clamped.
+fn to_int(value: u64) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
+impl EstimationResult {
+    fn new(file: String, label: String, output: EstimationOutput) -> Self {
+        EstimationResult {
+            file,
+            label,
+            status: output.status,
+            job_params: output.job_params.into(),
+            physical_counts: output.physical_counts.into(),
+            physical_counts_formatted: output.physical_counts_formatted.into(),
+            logical_qubit: output.logical_qubit.into(),
+            tfactory: output.tfactory.map(Into::into),
+            error_budget: output.error_budget.into(),
+            logical_counts: output.logical_counts.into(),
+            report_data: output.report_data.into(),
+            cache_hit: output.cache_hit,
+            budget_checks: Vec::new(),
+        }
+    }
+
+    fn with_budget_checks(mut self, checks: Vec<BudgetCheck>) -> Self {
+        self.budget_checks = checks;
+        self
+    }
+}
+
+impl FrontierResult {
+    fn new(file: String, label: String, output: FrontierOutput) -> Self {
+        FrontierResult {
+            file,
+            label,
+            csv: output.to_csv(),
+            status: output.status,
+            job_params: output.job_params.into(),
+            entries: output.frontier_entries.into_iter().map(Into::into).collect(),
+            logical_counts: output.logical_counts.into(),
+        }
+    }
+}
+
+impl SensitivityResult {
+    fn new(file: String, label: String, sensitivity: qcore::Sensitivity) -> Self {
+        SensitivityResult {
+            baseline: EstimationResult::new(file.clone(), label.clone(), sensitivity.baseline),
+            file,
+            label,
+            step: sensitivity.step,
+            elasticities: sensitivity.elasticities.into_iter().map(Into::into).collect(),
+            failures: sensitivity
+                .failures
+                .into_iter()
+                .map(|(parameter, message)| SensitivityFailure { parameter, message })
+                .collect(),
+        }
+    }
+}
+
+impl From<qcore::Elasticity> for Elasticity {
+    fn from(elasticity: qcore::Elasticity) -> Self {
+        Elasticity {
+            parameter: elasticity.parameter,
+            value: elasticity.value,
+            physical_qubits: elasticity.physical_qubits,
+            runtime: elasticity.runtime,
+        }
+    }
+}
+
+impl From<qcore::LimitCheck> for BudgetCheck {
+    fn from(check: qcore::LimitCheck) -> Self {
+        BudgetCheck {
+            message: check.to_string(),
+            passed: check.passed(),
+            metric: Some(check.limit.metric.to_string()),
+            max: Some(check.limit.max),
+            actual: Some(check.actual as f64),
+            location: check.limit.location.map(Into::into),
+        }
+    }
+}
+
+impl From<qcore::Error> for BudgetCheck {
+    fn from(error: qcore::Error) -> Self {
+        BudgetCheck {
+            message: error.to_string(),
+            passed: false,
+            metric: None,
+            max: None,
+            actual: None,
+            location: error
+                .diagnostics()
+                .into_iter()
+                .find_map(|diagnostic| diagnostic.locations.into_iter().next())
+                .map(Into::into),
+        }
+    }
+}
+
+impl From<qcore::SourceLocation> for SourceLocation {
+    fn from(location: qcore::SourceLocation) -> Self {
+        SourceLocation {
+            file: location.file,
+            line: to_int(location.line as u64),
+            column: to_int(location.column as u64),
+        }
+    }
+}
+
+/// A program's `// @budget` annotations: the limits of the well-formed ones, and the malformed
+/// ones as checks that fail, so they are reported with the estimate instead of failing it.
+struct Annotations {
+    budget: qcore::Budget,
+    malformed: Vec<BudgetCheck>,
+}
+
+impl Annotations {
+    fn read(program: &Program) -> Self {
+        let (budget, errors) = qcore::Budget::read_annotations(program);
+        Annotations {
+            budget,
+            malformed: errors.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    fn check(&self, output: &EstimationOutput) -> Vec<BudgetCheck> {
+        let checks = self.budget.check(output).into_iter().map(Into::into);
+        checks.chain(self.malformed.iter().cloned()).collect()
+    }
+}
+
+impl From<output::FrontierEntry> for FrontierEntry {
+    fn from(entry: output::FrontierEntry) -> Self {
+        FrontierEntry {
+            logical_qubit: entry.logical_qubit.into(),
+            tfactory: entry.tfactory.map(Into::into),
+            error_budget: entry.error_budget.into(),
+            physical_counts: entry.physical_counts.into(),
+            physical_counts_formatted: entry.physical_counts_formatted.into(),
+        }
+    }
+}
+
+impl From<output::JobParams> for JobParams {
+    fn from(params: output::JobParams) -> Self {
+        JobParams {
+            qec_scheme: params.qec_scheme.into(),
+            error_budget: params.error_budget.total(),
+            error_budget_partition: match params.error_budget {
+                qcore::ErrorBudget::Total(_) => None,
+                qcore::ErrorBudget::Partitioned { logical, t_states, rotations } => Some(ErrorBudget {
+                    logical,
+                    tstates: t_states,
+                    rotations,
+                }),
+            },
+            qubit_params: params.qubit_params.into(),
+            constraints: params.constraints.into(),
+            estimate_type: params.estimate_type,
+        }
+    }
+}
+
+impl From<qcore::catalog::Catalog> for Presets {
+    fn from(catalog: qcore::catalog::Catalog) -> Self {
+        Presets {
+            qubit_params: catalog
+                .qubit_params
+                .into_iter()
+                .map(|preset| QubitPreset {
+                    description: preset.description.to_string(),
+                    params: preset.params.into(),
+                })
+                .collect(),
+            qec_schemes: catalog
+                .qec_schemes
+                .into_iter()
+                .map(|preset| QecSchemePreset {
+                    description: preset.description.to_string(),
+                    instruction_set: preset.instruction_set.into(),
+                    params: preset.params.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<output::QecScheme> for QecScheme {
+    fn from(scheme: output::QecScheme) -> Self {
+        QecScheme {
+            name: scheme.name,
+            error_correction_threshold: scheme.error_correction_threshold,
+            crossing_prefactor: scheme.crossing_prefactor,
+            logical_cycle_time: scheme.logical_cycle_time,
+            physical_qubits_per_logical_qubit: scheme.physical_qubits_per_logical_qubit,
+            max_code_distance: to_float(scheme.max_code_distance),
+        }
+    }
+}
+
+impl From<output::QubitParams> for QubitParams {
+    fn from(params: output::QubitParams) -> Self {
+        QubitParams {
+            instruction_set: params.instruction_set,
+            name: params.name,
+            one_qubit_measurement_time: params.one_qubit_measurement_time,
+            one_qubit_gate_time: params.one_qubit_gate_time,
+            two_qubit_gate_time: params.two_qubit_gate_time,
+            two_qubit_joint_measurement_time: params.two_qubit_joint_measurement_time,
+            t_gate_time: params.t_gate_time,
+            one_qubit_measurement_error_rate: params.one_qubit_measurement_error_rate.into(),
+            one_qubit_gate_error_rate: params.one_qubit_gate_error_rate,
+            two_qubit_gate_error_rate: params.two_qubit_gate_error_rate,
+            two_qubit_joint_measurement_error_rate: params
+                .two_qubit_joint_measurement_error_rate
+                .map(Into::into),
+            t_gate_error_rate: params.t_gate_error_rate,
+            idle_error_rate: params.idle_error_rate,
+        }
+    }
+}
+
+impl From<output::MeasurementErrorRate> for MeasurementErrorRate {
+    fn from(rate: output::MeasurementErrorRate) -> Self {
+        MeasurementErrorRate {
+            process: rate.process,
+            readout: rate.readout,
+        }
+    }
+}
+
+impl From<output::Constraints> for Constraints {
+    fn from(constraints: output::Constraints) -> Self {
+        Constraints {
+            max_distillation_rounds: to_float(constraints.max_distillation_rounds),
+            logical_depth_factor: constraints.logical_depth_factor,
+            max_t_factories: constraints.max_t_factories.map(to_float),
+            max_duration: constraints.max_duration,
+            max_physical_qubits: constraints.max_physical_qubits.map(to_float),
+        }
+    }
+}
+
+impl From<output::PhysicalCounts> for PhysicalCounts {
+    fn from(counts: output::PhysicalCounts) -> Self {
+        PhysicalCounts {
+            physical_qubits: to_float(counts.physical_qubits),
+            runtime: to_float(counts.runtime),
+            rqops: to_float(counts.rqops),
+            breakdown: counts.breakdown.into(),
+        }
+    }
+}
+
+impl From<output::PhysicalCountsBreakdown> for PhysicalCountsBreakdown {
+    fn from(breakdown: output::PhysicalCountsBreakdown) -> Self {
+        PhysicalCountsBreakdown {
+            algorithmic_logical_qubits: to_float(breakdown.algorithmic_logical_qubits),
+            algorithmic_logical_depth: to_float(breakdown.algorithmic_logical_depth),
+            logical_depth: to_float(breakdown.logical_depth),
+            num_tstates: to_float(breakdown.num_tstates),
+            clock_frequency: breakdown.clock_frequency,
+            num_tfactories: to_float(breakdown.num_tfactories),
+            num_tfactory_runs: to_float(breakdown.num_tfactory_runs),
+            physical_qubits_for_tfactories: to_float(breakdown.physical_qubits_for_tfactories),
+            physical_qubits_for_algorithm: to_float(breakdown.physical_qubits_for_algorithm),
+            required_logical_qubit_error_rate: breakdown.required_logical_qubit_error_rate,
+            required_logical_tstate_error_rate: breakdown.required_logical_tstate_error_rate,
+            num_ts_per_rotation: breakdown.num_ts_per_rotation.map(to_float),
+            clifford_error_rate: breakdown.clifford_error_rate,
+        }
+    }
+}
+
+impl From<output::PhysicalCountsFormatted> for PhysicalCountsFormatted {
+    fn from(formatted: output::PhysicalCountsFormatted) -> Self {
+        PhysicalCountsFormatted {
+            runtime: formatted.runtime,
+            rqops: formatted.rqops,
+            physical_qubits: formatted.physical_qubits,
+            algorithmic_logical_qubits: formatted.algorithmic_logical_qubits,
+            algorithmic_logical_depth: formatted.algorithmic_logical_depth,
+            logical_depth: formatted.logical_depth,
+            num_tstates: formatted.num_tstates,
+            num_tfactories: formatted.num_tfactories,
+            num_tfactory_runs: formatted.num_tfactory_runs,
+            physical_qubits_for_algorithm: formatted.physical_qubits_for_algorithm,
+            physical_qubits_for_tfactories: formatted.physical_qubits_for_tfactories,
+            physical_qubits_for_tfactories_percentage: formatted.physical_qubits_for_tfactories_percentage,
+            required_logical_qubit_error_rate: formatted.required_logical_qubit_error_rate,
+            required_logical_tstate_error_rate: formatted.required_logical_tstate_error_rate,
+            physical_qubits_per_logical_qubit: formatted.physical_qubits_per_logical_qubit,
+            logical_cycle_time: formatted.logical_cycle_time,
+            clock_frequency: formatted.clock_frequency,
+            logical_error_rate: formatted.logical_error_rate,
+        }
+    }
+}
+
+impl From<output::LogicalQubit> for LogicalQubit {
+    fn from(qubit: output::LogicalQubit) -> Self {
+        LogicalQubit {
+            code_distance: to_float(qubit.code_distance),
+            physical_qubits: to_float(qubit.physical_qubits),
+            logical_cycle_time: to_float(qubit.logical_cycle_time),
+            logical_error_rate: qubit.logical_error_rate,
+        }
+    }
+}
+
+impl From<output::TFactory> for TFactory {
+    fn from(factory: output::TFactory) -> Self {
+        let to_floats = |values: Vec<u64>| values.into_iter().map(to_float).collect();
+        TFactory {
+            physical_qubits: to_float(factory.physical_qubits),
+            runtime: to_float(factory.runtime),
+            num_tstates: to_float(factory.num_tstates),
+            num_input_tstates: to_float(factory.num_input_tstates),
+            num_rounds: to_float(factory.num_rounds),
+            num_units_per_round: to_floats(factory.num_units_per_round),
+            unit_name_per_round: factory.unit_name_per_round,
+            code_distance_per_round: to_floats(factory.code_distance_per_round),
+            physical_qubits_per_round: to_floats(factory.physical_qubits_per_round),
+            runtime_per_round: to_floats(factory.runtime_per_round),
+            logical_error_rate: factory.logical_error_rate,
+        }
+    }
+}
+
+impl From<output::ErrorBudget> for ErrorBudget {
+    fn from(budget: output::ErrorBudget) -> Self {
+        ErrorBudget {
+            logical: budget.logical,
+            tstates: budget.tstates,
+            rotations: budget.rotations,
+        }
+    }
+}
+
+impl From<output::LogicalCounts> for LogicalCounts {
+    fn from(counts: output::LogicalCounts) -> Self {
+        LogicalCounts {
+            num_qubits: to_float(counts.num_qubits),
+            t_count: to_float(counts.t_count),
+            rotation_count: to_float(counts.rotation_count),
+            rotation_depth: to_float(counts.rotation_depth),
+            ccz_count: to_float(counts.ccz_count),
+            ccix_count: to_float(counts.ccix_count),
+            measurement_count: to_float(counts.measurement_count),
+        }
+    }
+}
+
+impl From<output::ReportData> for ReportData {
+    fn from(data: output::ReportData) -> Self {
+        ReportData {
+            groups: data.groups.into_iter().map(Into::into).collect(),
+            assumptions: data.assumptions,
+        }
+    }
+}
+
+impl From<output::ReportGroup> for ReportGroup {
+    fn from(group: output::ReportGroup) -> Self {
+        ReportGroup {
+            title: group.title,
+            always_visible: group.always_visible,
+            entries: group.entries.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<output::ReportEntry> for ReportEntry {
+    fn from(entry: output::ReportEntry) -> Self {
+        ReportEntry {
+            path: entry.path,
+            label: entry.label,
+            description: entry.description,
+            explanation: entry.explanation,
+        }
+    }
+}
+
+/// Server-wide state every resolver can use.
+pub struct Context {
+    pub registry: Option<qcore::Registry>,
+    pub cache: Arc<qcore::ResultCache>,
+}
+
+impl juniper::Context for Context {}
+
+impl Context {
+    /// The params of one estimation: given explicitly, or those of a hardware profile
+    /// from the registry.
+    fn params(&self, params: Option<ParamsInput>, hardware_profile: Option<String>) -> FieldResult<qcore::Params> {
+        match (params, hardware_profile) {
+            (Some(params), None) => qcore::Params::try_from(params),
+            (None, Some(name)) => {
+                let registry = self.registry.as_ref().ok_or_else(|| {
+                    FieldError::from(format!(
+                        "this server has no profile registry; set {} to use hardware profiles",
+                        qcore::registry::REGISTRY_ENV
+                    ))
+                })?;
+                Ok(registry.get(&name).map_err(field_error)?.params.clone())
+            }
+            (Some(_), Some(_)) => Err(FieldError::from("give either params or hardwareProfile, not both")),
+            (None, None) => Err(FieldError::from("params or hardwareProfile is required")),
+        }
+    }
+}
+
 pub struct Query;
 
-#[juniper::graphql_object]
+#[juniper::graphql_object(context = Context)]
 impl Query {
-    fn estimation_result() -> FieldResult<EstimationResult> {
-        Ok(EstimationResult {
-            file: "www.google.com".to_string(),
-            status: "Success".to_string(),
-            job_params: JobParams {
-                qec_scheme: QecScheme {
-                    name: "Example".to_string(),
-                    error_correction_threshold: 0.1,
-                    crossing_prefactor: 1.0,
-                    logical_cycle_time: "1ns".to_string(),
-                    physical_qubits_per_logical_qubit: "100".to_string(),
-                    max_code_distance: 5,
-                },
-                error_budget: 0.01,
-                qubit_params: QubitParams {
-                    instruction_set: "Set".to_string(),
-                    name: "Qubit".to_string(),
-                    one_qubit_measurement_time: "10ns".to_string(),
-                    one_qubit_gate_time: "10ns".to_string(),
-                    two_qubit_gate_time: "20ns".to_string(),
-                    t_gate_time: "30ns".to_string(),
-                    one_qubit_measurement_error_rate: 0.001,
-                    one_qubit_gate_error_rate: 0.002,
-                    two_qubit_gate_error_rate: 0.003,
-                    t_gate_error_rate: 0.004,
-                    idle_error_rate: 0.005,
-                },
-                constraints: Constraints {
-                    max_distillation_rounds: 3,
-                },
-                estimate_type: "type".to_string(),
-            },
-            physical_counts: PhysicalCounts {
-                physical_qubits: 1000,
-                runtime: 1000,
-                rqops: 1000,
-                breakdown: PhysicalCountsBreakdown {
-                    algorithmic_logical_qubits: 50,
-                    algorithmic_logical_depth: 500,
-                    logical_depth: 500,
-                    num_tstates: 100,
-                    clock_frequency: 1.0,
-                    num_tfactories: 10,
-                    num_tfactory_runs: 100,
-                    physical_qubits_for_tfactories: 1000,
-                    physical_qubits_for_algorithm: 5000,
-                    required_logical_qubit_error_rate: 0.0001,
-                    required_logical_tstate_error_rate: None,
-                    num_ts_per_rotation: None,
-                    clifford_error_rate: 0.001,
-                },
-            },
-            physical_counts_formatted: PhysicalCountsFormatted {
-                runtime: "1000s".to_string(),
-                rqops: "1000".to_string(),
-                physical_qubits: "1000".to_string(),
-                algorithmic_logical_qubits: "50".to_string(),
-                algorithmic_logical_depth: "500".to_string(),
-                logical_depth: "500".to_string(),
-                num_tstates: "100".to_string(),
-                num_tfactories: "10".to_string(),
-                num_tfactory_runs: "100".to_string(),
-                physical_qubits_for_algorithm: "5000".to_string(),
-                physical_qubits_for_tfactories: "1000".to_string(),
-                physical_qubits_for_tfactories_percentage: "20%".to_string(),
-                required_logical_qubit_error_rate: "0.0001".to_string(),
-                required_logical_tstate_error_rate: "N/A".to_string(),
-                physical_qubits_per_logical_qubit: "100".to_string(),
-                logical_cycle_time: "1ns".to_string(),
-                clock_frequency: "1GHz".to_string(),
-                logical_error_rate: "0.0001".to_string(),
-            },
-            logical_qubit: LogicalQubit {
-                code_distance: 5,
-                physical_qubits: 100,
-                logical_cycle_time: 100,
-                logical_error_rate: 0.0001,
-            },
-            error_budget: ErrorBudget {
-                logical: 0.01,
-                tstates: 0.02,
-                rotations: 0.03,
-            },
-            logical_counts: LogicalCounts {
-                num_qubits: 10,
-                t_count: 100,
-                rotation_count: 100,
-                rotation_depth: 100,
-                ccz_count: 10,
-                ccix_count: 5,
-                measurement_count: 1000,
-            },
-            report_data: ReportData {
-                groups: vec![ReportGroup {
-                    title: "Group1".to_string(),
-                    always_visible: true,
-                    entries: vec![ReportEntry {
-                        path: "/path1".to_string(),
-                        label: "Label1".to_string(),
-                        description: "Description1".to_string(),
-                        explanation: "Explanation1".to_string(),
-                    }],
-                }],
-                assumptions: vec!["Assumption1".to_string()],
-            },
-        })
+    /// Estimates `file` under the default qubit model and QEC scheme.
+    async fn estimation_result(context: &Context, file: String) -> FieldResult<EstimationResult> {
+        run_estimation(context, file, None, &qcore::default_estimation_config()).await
+    }
+
+    /// Lists the built-in qubit models and QEC schemes with their full parameters.
+    fn presets() -> FieldResult<Presets> {
+        qcore::catalog::catalog().map(Presets::from).map_err(field_error)
     }
 }
 
@@ -265,9 +738,74 @@ impl Query {
 #[graphql(description = "Input data for estimation")]
 struct EstimationInput {
     file: String,
+    #[graphql(description = "Expression to estimate instead of the @EntryPoint() operation, e.g. \"Sample.QFT(64)\"")]
+    entry: Option<String>,
+    label: String,
+    detail: String,
+    #[graphql(description = "Target profile to compile the program for; defaults to ADAPTIVE_RI")]
+    profile: Option<TargetProfile>,
+    #[graphql(description = "Named hardware profile from the server's profile registry, instead of params")]
+    hardware_profile: Option<String>,
+    params: Option<ParamsInput>,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Input data for estimating an algorithm from its logical counts, without a Q# program")]
+struct CountsEstimationInput {
     label: String,
     detail: String,
-    params: ParamsInput,
+    counts: LogicalCountsInput,
+    #[graphql(description = "Named hardware profile from the server's profile registry, instead of params")]
+    hardware_profile: Option<String>,
+    params: Option<ParamsInput>,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Logical resource counts of an algorithm; counts left out are 0")]
+struct LogicalCountsInput {
+    num_qubits: i32,
+    t_count: Option<i32>,
+    rotation_count: Option<i32>,
+    rotation_depth: Option<i32>,
+    ccz_count: Option<i32>,
+    ccix_count: Option<i32>,
+    measurement_count: Option<i32>,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "One program estimated under several configurations")]
+struct BatchEstimationInput {
+    file: String,
+    #[graphql(description = "Expression to estimate instead of the @EntryPoint() operation, e.g. \"Sample.QFT(64)\"")]
+    entry: Option<String>,
+    items: Vec<EstimationItemInput>,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Every combination of the listed qubit models, QEC schemes, error budgets and constraints. \
+    A missing list leaves that parameter at its default")]
+struct SweepInput {
+    file: String,
+    #[graphql(description = "Expression to estimate instead of the @EntryPoint() operation, e.g. \"Sample.QFT(64)\"")]
+    entry: Option<String>,
+    #[graphql(description = "Target profile to compile the program for; defaults to ADAPTIVE_RI")]
+    profile: Option<TargetProfile>,
+    qubit_params: Option<Vec<QubitParamsInput>>,
+    qec_schemes: Option<Vec<QecSchemeInput>>,
+    error_budgets: Option<Vec<ErrorBudgetInput>>,
+    constraints: Option<Vec<ConstraintsInput>>,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "One configuration of a batch estimation")]
+struct EstimationItemInput {
+    label: String,
+    detail: String,
+    #[graphql(description = "Target profile to compile the program for; defaults to ADAPTIVE_RI")]
+    profile: Option<TargetProfile>,
+    #[graphql(description = "Named hardware profile from the server's profile registry, instead of params")]
+    hardware_profile: Option<String>,
+    params: Option<ParamsInput>,
 }
 
 #[derive(GraphQLInputObject)]
@@ -275,134 +813,599 @@ struct EstimationInput {
 struct ParamsInput {
     qubit_params: QubitParamsInput,
     qec_scheme: QecSchemeInput,
+    error_budget: Option<ErrorBudgetInput>,
+    constraints: Option<ConstraintsInput>,
+    distillation_unit_specifications: Option<Vec<DistillationUnitSpecificationInput>>,
 }
 
 #[derive(GraphQLInputObject)]
-#[graphql(description = "Qubit parameters for the estimation")]
+#[graphql(description = "Error budget, either as a `total` or split into `logical`, `tStates` and `rotations`")]
+struct ErrorBudgetInput {
+    total: Option<f64>,
+    logical: Option<f64>,
+    t_states: Option<f64>,
+    rotations: Option<f64>,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Estimator constraints. `maxDuration` and `maxPhysicalQubits` are mutually exclusive")]
+struct ConstraintsInput {
+    #[graphql(description = "Factor of at least 1 to stretch the logical depth by")]
+    logical_depth_factor: Option<f64>,
+    max_t_factories: Option<i32>,
+    #[graphql(description = "Time with unit, e.g. \"1 s\"")]
+    max_duration: Option<String>,
+    max_physical_qubits: Option<f64>,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Magic state distillation unit: either the `name` of a built-in unit or a custom protocol. \
+    Formulas may use `inputErrorRate`, `cliffordErrorRate` and `readoutErrorRate`")]
+struct DistillationUnitSpecificationInput {
+    name: Option<String>,
+    display_name: Option<String>,
+    num_input_ts: Option<i32>,
+    num_output_ts: Option<i32>,
+    failure_probability_formula: Option<String>,
+    output_error_rate_formula: Option<String>,
+    physical_qubit_specification: Option<DistillationUnitQubitSpecificationInput>,
+    logical_qubit_specification: Option<DistillationUnitQubitSpecificationInput>,
+    logical_qubit_specification_first_round_override: Option<DistillationUnitQubitSpecificationInput>,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Qubits and duration (in qubit cycles) one distillation unit takes")]
+struct DistillationUnitQubitSpecificationInput {
+    num_unit_qubits: i32,
+    duration_in_qubit_cycle_time: i32,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Qubit parameters for the estimation. Leave the optional fields out to use the named preset \
+    as is, or set `instructionSet` and the matching times and error rates to describe a custom qubit")]
 struct QubitParamsInput {
     name: String,
+    instruction_set: Option<InstructionSet>,
+    #[graphql(description = "Time with unit, e.g. \"100 ns\"")]
+    one_qubit_measurement_time: Option<String>,
+    one_qubit_gate_time: Option<String>,
+    two_qubit_gate_time: Option<String>,
+    two_qubit_joint_measurement_time: Option<String>,
+    t_gate_time: Option<String>,
+    one_qubit_measurement_error_rate: Option<MeasurementErrorRateInput>,
+    one_qubit_gate_error_rate: Option<f64>,
+    two_qubit_gate_error_rate: Option<f64>,
+    two_qubit_joint_measurement_error_rate: Option<MeasurementErrorRateInput>,
+    t_gate_error_rate: Option<f64>,
+    idle_error_rate: Option<f64>,
+}
+
+#[derive(GraphQLEnum, Clone, Copy)]
+enum InstructionSet {
+    GateBased,
+    Majorana,
+}
+
+#[derive(GraphQLEnum, Clone, Copy)]
+#[graphql(description = "How much classical computation on measurement results the target supports")]
+enum TargetProfile {
+    #[graphql(description = "No branching on measurement results")]
+    Base,
+    #[graphql(name = "ADAPTIVE_RI", description = "Branching on measurement results and integer computations")]
+    AdaptiveRi,
+    #[graphql(description = "Any Q# program")]
+    Unrestricted,
 }
 
 #[derive(GraphQLInputObject)]
-#[graphql(description = "QEC scheme details")]
+#[graphql(description = "Measurement error rate split into process and readout errors")]
+struct MeasurementErrorRateInput {
+    process: f64,
+    readout: f64,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "QEC scheme details. Leave the optional fields out to use the named preset as is; \
+    a custom scheme sets the threshold, crossing prefactor and both formulas")]
 struct QecSchemeInput {
     name: String,
+    error_correction_threshold: Option<f64>,
+    crossing_prefactor: Option<f64>,
+    #[graphql(description = "Formula in `codeDistance` and the qubit's operation times")]
+    logical_cycle_time: Option<String>,
+    #[graphql(description = "Formula in `codeDistance`")]
+    physical_qubits_per_logical_qubit: Option<String>,
+    max_code_distance: Option<i32>,
+}
+
+impl TryFrom<ParamsInput> for qcore::Params {
+    type Error = FieldError;
+
+    fn try_from(input: ParamsInput) -> Result<Self, Self::Error> {
+        Ok(qcore::Params {
+            qubit_params: input.qubit_params.into(),
+            qec_scheme: input.qec_scheme.into(),
+            error_budget: input.error_budget.map(qcore::ErrorBudget::try_from).transpose()?,
+            constraints: input.constraints.map(qcore::Constraints::try_from).transpose()?,
+            distillation_unit_specifications: input
+                .distillation_unit_specifications
+                .map(|units| {
+                    units
+                        .into_iter()
+                        .map(qcore::DistillationUnitSpecification::try_from)
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SweepInput> for qcore::SweepSpec {
+    type Error = FieldError;
+
+    fn try_from(input: SweepInput) -> Result<Self, Self::Error> {
+        Ok(qcore::SweepSpec {
+            profile: input.profile.map(Into::into).unwrap_or_default(),
+            qubit_params: input.qubit_params.unwrap_or_default().into_iter().map(Into::into).collect(),
+            qec_schemes: input.qec_schemes.unwrap_or_default().into_iter().map(Into::into).collect(),
+            error_budgets: input
+                .error_budgets
+                .unwrap_or_default()
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<FieldResult<_>>()?,
+            constraints: input
+                .constraints
+                .unwrap_or_default()
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<FieldResult<_>>()?,
+            threads: None,
+        })
+    }
+}
+
+impl TryFrom<ErrorBudgetInput> for qcore::ErrorBudget {
+    type Error = FieldError;
+
+    fn try_from(input: ErrorBudgetInput) -> Result<Self, Self::Error> {
+        match input {
+            ErrorBudgetInput { total: Some(total), logical: None, t_states: None, rotations: None } => {
+                Ok(qcore::ErrorBudget::Total(total))
+            }
+            ErrorBudgetInput { total: None, logical: Some(logical), t_states: Some(t_states), rotations: Some(rotations) } => {
+                Ok(qcore::ErrorBudget::Partitioned { logical, t_states, rotations })
+            }
+            _ => Err(FieldError::from(
+                "errorBudget takes either `total` or all of `logical`, `tStates` and `rotations`",
+            )),
+        }
+    }
+}
+
+impl TryFrom<ConstraintsInput> for qcore::Constraints {
+    type Error = FieldError;
+
+    fn try_from(input: ConstraintsInput) -> Result<Self, Self::Error> {
+        Ok(qcore::Constraints {
+            logical_depth_factor: input.logical_depth_factor,
+            max_t_factories: input
+                .max_t_factories
+                .map(|max| u32::try_from(max).map_err(|_| "constraints: maxTFactories must not be negative"))
+                .transpose()?,
+            max_duration: input.max_duration,
+            max_physical_qubits: input
+                .max_physical_qubits
+                .map(|max| to_count("constraints: maxPhysicalQubits", max))
+                .transpose()?,
+        })
+    }
+}
+
+/// Counts that may exceed an `Int` come in as `Float`; anything but a whole number that
+/// fits in a `Float` exactly is rejected here rather than rounded, the core types are unsigned.
+fn to_count(field: &str, value: f64) -> FieldResult<u64> {
+    if value < 0.0 {
+        return Err(FieldError::from(format!("{field} must not be negative, got {value}")));
+    }
+    if value.fract() != 0.0 || value > 2f64.powi(53) {
+        return Err(FieldError::from(format!("{field} must be a whole number up to 2^53, got {value}")));
+    }
+    Ok(value as u64)
+}
+
+impl TryFrom<LogicalCountsInput> for qcore::LogicalCounts {
+    type Error = FieldError;
+
+    fn try_from(input: LogicalCountsInput) -> Result<Self, Self::Error> {
+        let optional = |field, value: Option<i32>| to_count(field, value.unwrap_or(0));
+        Ok(qcore::LogicalCounts {
+            num_qubits: to_count("numQubits", input.num_qubits)?,
+            t_count: optional("tCount", input.t_count)?,
+            rotation_count: optional("rotationCount", input.rotation_count)?,
+            rotation_depth: optional("rotationDepth", input.rotation_depth)?,
+            ccz_count: optional("cczCount", input.ccz_count)?,
+            ccix_count: optional("ccixCount", input.ccix_count)?,
+            measurement_count: optional("measurementCount", input.measurement_count)?,
+        })
+    }
+}
+
+impl TryFrom<DistillationUnitSpecificationInput> for qcore::DistillationUnitSpecification {
+    type Error = FieldError;
+
+    fn try_from(input: DistillationUnitSpecificationInput) -> Result<Self, Self::Error> {
+        Ok(qcore::DistillationUnitSpecification {
+            name: input.name,
+            display_name: input.display_name,
+            num_input_ts: input.num_input_ts.map(|n| to_count("numInputTs", n.into())).transpose()?,
+            num_output_ts: input.num_output_ts.map(|n| to_count("numOutputTs", n.into())).transpose()?,
+            failure_probability_formula: input.failure_probability_formula,
+            output_error_rate_formula: input.output_error_rate_formula,
+            physical_qubit_specification: input
+                .physical_qubit_specification
+                .map(qcore::DistillationUnitQubitSpecification::try_from)
+                .transpose()?,
+            logical_qubit_specification: input
+                .logical_qubit_specification
+                .map(qcore::DistillationUnitQubitSpecification::try_from)
+                .transpose()?,
+            logical_qubit_specification_first_round_override: input
+                .logical_qubit_specification_first_round_override
+                .map(qcore::DistillationUnitQubitSpecification::try_from)
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<DistillationUnitQubitSpecificationInput> for qcore::DistillationUnitQubitSpecification {
+    type Error = FieldError;
+
+    fn try_from(input: DistillationUnitQubitSpecificationInput) -> Result<Self, Self::Error> {
+        Ok(qcore::DistillationUnitQubitSpecification {
+            num_unit_qubits: to_count("numUnitQubits", input.num_unit_qubits.into())?,
+            duration_in_qubit_cycle_time: to_count("durationInQubitCycleTime", input.duration_in_qubit_cycle_time.into())?,
+        })
+    }
+}
+
+impl From<QubitParamsInput> for qcore::QubitParams {
+    fn from(input: QubitParamsInput) -> Self {
+        qcore::QubitParams {
+            name: input.name,
+            instruction_set: input.instruction_set.map(Into::into),
+            one_qubit_measurement_time: input.one_qubit_measurement_time,
+            one_qubit_gate_time: input.one_qubit_gate_time,
+            two_qubit_gate_time: input.two_qubit_gate_time,
+            two_qubit_joint_measurement_time: input.two_qubit_joint_measurement_time,
+            t_gate_time: input.t_gate_time,
+            one_qubit_measurement_error_rate: input.one_qubit_measurement_error_rate.map(Into::into),
+            one_qubit_gate_error_rate: input.one_qubit_gate_error_rate,
+            two_qubit_gate_error_rate: input.two_qubit_gate_error_rate,
+            two_qubit_joint_measurement_error_rate: input.two_qubit_joint_measurement_error_rate.map(Into::into),
+            t_gate_error_rate: input.t_gate_error_rate,
+            idle_error_rate: input.idle_error_rate,
+        }
+    }
+}
+
+impl From<QecSchemeInput> for qcore::QecScheme {
+    fn from(input: QecSchemeInput) -> Self {
+        qcore::QecScheme {
+            name: input.name,
+            error_correction_threshold: input.error_correction_threshold,
+            crossing_prefactor: input.crossing_prefactor,
+            logical_cycle_time: input.logical_cycle_time,
+            physical_qubits_per_logical_qubit: input.physical_qubits_per_logical_qubit,
+            // Negative distances become 0, which validation rejects.
+            max_code_distance: input.max_code_distance.map(|d| u32::try_from(d).unwrap_or(0)),
+        }
+    }
+}
+
+impl From<qcore::InstructionSet> for InstructionSet {
+    fn from(instruction_set: qcore::InstructionSet) -> Self {
+        match instruction_set {
+            qcore::InstructionSet::GateBased => InstructionSet::GateBased,
+            qcore::InstructionSet::Majorana => InstructionSet::Majorana,
+        }
+    }
+}
+
+impl From<InstructionSet> for qcore::InstructionSet {
+    fn from(instruction_set: InstructionSet) -> Self {
+        match instruction_set {
+            InstructionSet::GateBased => qcore::InstructionSet::GateBased,
+            InstructionSet::Majorana => qcore::InstructionSet::Majorana,
+        }
+    }
+}
+
+impl From<TargetProfile> for qcore::TargetProfile {
+    fn from(profile: TargetProfile) -> Self {
+        match profile {
+            TargetProfile::Base => qcore::TargetProfile::Base,
+            TargetProfile::AdaptiveRi => qcore::TargetProfile::AdaptiveRI,
+            TargetProfile::Unrestricted => qcore::TargetProfile::Unrestricted,
+        }
+    }
+}
+
+impl From<MeasurementErrorRateInput> for qcore::MeasurementErrorRate {
+    fn from(input: MeasurementErrorRateInput) -> Self {
+        qcore::MeasurementErrorRate {
+            process: input.process,
+            readout: input.readout,
+        }
+    }
 }
 
 pub struct Mutation;
 
-#[juniper::graphql_object]
+/// Largest sweep one request may ask for, as every point is estimated before responding.
+const MAX_SWEEP_POINTS: usize = 1000;
+
+#[juniper::graphql_object(context = Context)]
 impl Mutation {
-    fn create_estimation(estimation: EstimationInput) -> FieldResult<EstimationResult> {
-        Ok(EstimationResult {
-            file: "www.google.com".to_string(),
-            status: "Success".to_string(),
-            job_params: JobParams {
-                qec_scheme: QecScheme {
-                    name: "Example".to_string(),
-                    error_correction_threshold: 0.1,
-                    crossing_prefactor: 1.0,
-                    logical_cycle_time: "1ns".to_string(),
-                    physical_qubits_per_logical_qubit: "100".to_string(),
-                    max_code_distance: 5,
-                },
-                error_budget: 0.01,
-                qubit_params: QubitParams {
-                    instruction_set: "Set".to_string(),
-                    name: "Qubit".to_string(),
-                    one_qubit_measurement_time: "10ns".to_string(),
-                    one_qubit_gate_time: "10ns".to_string(),
-                    two_qubit_gate_time: "20ns".to_string(),
-                    t_gate_time: "30ns".to_string(),
-                    one_qubit_measurement_error_rate: 0.001,
-                    one_qubit_gate_error_rate: 0.002,
-                    two_qubit_gate_error_rate: 0.003,
-                    t_gate_error_rate: 0.004,
-                    idle_error_rate: 0.005,
-                },
-                constraints: Constraints {
-                    max_distillation_rounds: 3,
-                },
-                estimate_type: "type".to_string(),
-            },
-            physical_counts: PhysicalCounts {
-                physical_qubits: 1000,
-                runtime: 1000,
-                rqops: 1000,
-                breakdown: PhysicalCountsBreakdown {
-                    algorithmic_logical_qubits: 50,
-                    algorithmic_logical_depth: 500,
-                    logical_depth: 500,
-                    num_tstates: 100,
-                    clock_frequency: 1.0,
-                    num_tfactories: 10,
-                    num_tfactory_runs: 100,
-                    physical_qubits_for_tfactories: 1000,
-                    physical_qubits_for_algorithm: 5000,
-                    required_logical_qubit_error_rate: 0.0001,
-                    required_logical_tstate_error_rate: None,
-                    num_ts_per_rotation: None,
-                    clifford_error_rate: 0.001,
-                },
-            },
-            physical_counts_formatted: PhysicalCountsFormatted {
-                runtime: "1000s".to_string(),
-                rqops: "1000".to_string(),
-                physical_qubits: "1000".to_string(),
-                algorithmic_logical_qubits: "50".to_string(),
-                algorithmic_logical_depth: "500".to_string(),
-                logical_depth: "500".to_string(),
-                num_tstates: "100".to_string(),
-                num_tfactories: "10".to_string(),
-                num_tfactory_runs: "100".to_string(),
-                physical_qubits_for_algorithm: "5000".to_string(),
-                physical_qubits_for_tfactories: "1000".to_string(),
-                physical_qubits_for_tfactories_percentage: "20%".to_string(),
-                required_logical_qubit_error_rate: "0.0001".to_string(),
-                required_logical_tstate_error_rate: "N/A".to_string(),
-                physical_qubits_per_logical_qubit: "100".to_string(),
-                logical_cycle_time: "1ns".to_string(),
-                clock_frequency: "1GHz".to_string(),
-                logical_error_rate: "0.0001".to_string(),
-            },
-            logical_qubit: LogicalQubit {
-                code_distance: 5,
-                physical_qubits: 100,
-                logical_cycle_time: 100,
-                logical_error_rate: 0.0001,
-            },
-            error_budget: ErrorBudget {
-                logical: 0.01,
-                tstates: 0.02,
-                rotations: 0.03,
-            },
-            logical_counts: LogicalCounts {
-                num_qubits: 10,
-                t_count: 100,
-                rotation_count: 100,
-                rotation_depth: 100,
-                ccz_count: 10,
-                ccix_count: 5,
-                measurement_count: 1000,
-            },
-            report_data: ReportData {
-                groups: vec![ReportGroup {
-                    title: "Group1".to_string(),
-                    always_visible: true,
-                    entries: vec![ReportEntry {
-                        path: "/path1".to_string(),
-                        label: "Label1".to_string(),
-                        description: "Description1".to_string(),
-                        explanation: "Explanation1".to_string(),
-                    }],
-                }],
-                assumptions: vec!["Assumption1".to_string()],
-            },
+    async fn create_estimation(context: &Context, estimation: EstimationInput) -> FieldResult<EstimationResult> {
+        let config = EstimationConfig {
+            label: estimation.label,
+            detail: estimation.detail,
+            profile: estimation.profile.map(Into::into).unwrap_or_default(),
+            hardware: None,
+            params: context.params(estimation.params, estimation.hardware_profile)?,
+        };
+        run_estimation(context, estimation.file, estimation.entry, &config).await
+    }
+
+    /// Estimates an algorithm from its logical counts alone; `file` in the result is empty.
+    async fn create_estimation_from_counts(context: &Context, estimation: CountsEstimationInput) -> FieldResult<EstimationResult> {
+        let config = EstimationConfig {
+            label: estimation.label,
+            detail: estimation.detail,
+            profile: qcore::TargetProfile::default(),
+            hardware: None,
+            params: context.params(estimation.params, estimation.hardware_profile)?,
+        };
+        let counts = qcore::LogicalCounts::try_from(estimation.counts)?;
+
+        blocking(move || {
+            let output = qcore::estimate_logical_counts(&counts, &config).map_err(field_error)?;
+            Ok(EstimationResult::new(String::new(), config.label, output))
         })
+        .await
     }
+
+    /// Estimates the qubits/runtime frontier of `estimation.file` instead of a single point.
+    async fn create_frontier_estimation(context: &Context, estimation: EstimationInput) -> FieldResult<FrontierResult> {
+        let config = EstimationConfig {
+            label: estimation.label,
+            detail: estimation.detail,
+            profile: estimation.profile.map(Into::into).unwrap_or_default(),
+            hardware: None,
+            params: context.params(estimation.params, estimation.hardware_profile)?,
+        };
+
+        let program = fetch_program(&estimation.file, estimation.entry).await?;
+
+        blocking(move || {
+            let output = qcore::estimate_program_frontier(&program, &config).map_err(field_error)?;
+            Ok(FrontierResult::new(estimation.file, config.label, output))
+        })
+        .await
+    }
+
+    /// Ranks the qubit and QEC parameters by how much the estimate of `estimation.file`
+    /// changes with each. `step` is the relative change to perturb them by, 0.1 by default.
+    async fn create_sensitivity_analysis(
+        context: &Context,
+        estimation: EstimationInput,
+        step: Option<f64>,
+    ) -> FieldResult<SensitivityResult> {
+        let config = EstimationConfig {
+            label: estimation.label,
+            detail: estimation.detail,
+            profile: estimation.profile.map(Into::into).unwrap_or_default(),
+            hardware: None,
+            params: context.params(estimation.params, estimation.hardware_profile)?,
+        };
+
+        let program = fetch_program(&estimation.file, estimation.entry).await?;
+        let step = step.unwrap_or(qcore::sensitivity::DEFAULT_STEP);
+
+        blocking(move || {
+            let sensitivity = qcore::estimate_program_sensitivity(&program, &config, step).map_err(field_error)?;
+            Ok(SensitivityResult::new(estimation.file, config.label, sensitivity))
+        })
+        .await
+    }
+
+    /// Like `createSensitivityAnalysis`, for an algorithm known only by its logical counts.
+    async fn create_sensitivity_analysis_from_counts(
+        context: &Context,
+        estimation: CountsEstimationInput,
+        step: Option<f64>,
+    ) -> FieldResult<SensitivityResult> {
+        let config = EstimationConfig {
+            label: estimation.label,
+            detail: estimation.detail,
+            profile: qcore::TargetProfile::default(),
+            hardware: None,
+            params: context.params(estimation.params, estimation.hardware_profile)?,
+        };
+        let counts = qcore::LogicalCounts::try_from(estimation.counts)?;
+        let step = step.unwrap_or(qcore::sensitivity::DEFAULT_STEP);
+
+        blocking(move || {
+            let sensitivity = qcore::estimate_logical_counts_sensitivity(&counts, &config, step).map_err(field_error)?;
+            Ok(SensitivityResult::new(String::new(), config.label, sensitivity))
+        })
+        .await
+    }
+
+    /// Estimates one file under every item's configuration, compiling it once per target profile.
+    /// Results are in the order of `items`; items that fail carry their error instead, only a
+    /// program that fails to compile or run fails the whole batch.
+    async fn create_estimations(context: &Context, estimation: BatchEstimationInput) -> FieldResult<Vec<BatchItemResult>> {
+        let mut configs = Vec::new();
+        let items: Vec<(String, Result<(), String>)> = estimation
+            .items
+            .into_iter()
+            .map(|item| {
+                let params = context.params(item.params, item.hardware_profile);
+                let config = params.map_err(|error| error.message().to_string()).map(|params| {
+                    configs.push(EstimationConfig {
+                        label: item.label.clone(),
+                        detail: item.detail,
+                        profile: item.profile.map(Into::into).unwrap_or_default(),
+                        hardware: None,
+                        params,
+                    });
+                });
+                (item.label, config)
+            })
+            .collect();
+
+        let program = fetch_program(&estimation.file, estimation.entry).await?;
+        let annotations = Annotations::read(&program);
+        let cache = context.cache.clone();
+
+        blocking(move || {
+            let mut outputs = cache.estimate_batch(&program, &configs).map_err(field_error)?.into_iter();
+            Ok(items
+                .into_iter()
+                .map(|(label, config)| {
+                    let output = config.and_then(|()| {
+                        let output = outputs.next().expect("one result per valid configuration");
+                        output.map_err(|error| error.to_string())
+                    });
+                    let (result, error) = match output {
+                        Ok(output) => {
+                            let checks = annotations.check(&output);
+                            let result = EstimationResult::new(estimation.file.clone(), label.clone(), output);
+                            (Some(result.with_budget_checks(checks)), None)
+                        }
+                        Err(error) => (None, Some(error)),
+                    };
+                    BatchItemResult { label, result, error }
+                })
+                .collect())
+        })
+        .await
+    }
+
+    /// Estimates `sweep.file` under every combination of the sweep's parameters, in parallel.
+    /// Results are in the order of their `index`; points that fail carry their error instead.
+    async fn create_sweep(sweep: SweepInput) -> FieldResult<Vec<SweepPointResult>> {
+        let file = sweep.file.clone();
+        let entry = sweep.entry.clone();
+        let spec = qcore::SweepSpec::try_from(sweep)?;
+        // Checked before building the configurations, which a huge sweep would run out of memory on.
+        match spec.size() {
+            Some(size) if size <= MAX_SWEEP_POINTS => {}
+            size => {
+                let size = size.map_or_else(|| "too many".to_string(), |size| size.to_string());
+                return Err(FieldError::from(format!(
+                    "the sweep has {size} points, more than the {MAX_SWEEP_POINTS} this server estimates at once"
+                )));
+            }
+        }
+        let program = fetch_program(&file, entry).await?;
+        let annotations = Annotations::read(&program);
+
+        let mut points = blocking(move || {
+            let mut points = Vec::new();
+            qcore::estimate_program_sweep(&program, &spec, |point| points.push(point)).map_err(field_error)?;
+            Ok(points)
+        })
+        .await?;
+        points.sort_by_key(|point| point.index);
+
+        Ok(points
+            .into_iter()
+            .map(|point| {
+                let (result, error) = match point.output {
+                    Ok(output) => {
+                        let checks = annotations.check(&output);
+                        let result = EstimationResult::new(file.clone(), point.config.label.clone(), output);
+                        (Some(result.with_budget_checks(checks)), None)
+                    }
+                    Err(error) => (None, Some(error.to_string())),
+                };
+                SweepPointResult {
+                    index: i32::try_from(point.index).unwrap_or(i32::MAX),
+                    label: point.config.label,
+                    result,
+                    error,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Fetches the Q# file at `file` and runs it through the resource estimator, unless the
+/// result is cached, then checks the file's budget annotations against the result.
+async fn run_estimation(context: &Context, file: String, entry: Option<String>, config: &EstimationConfig) -> FieldResult<EstimationResult> {
+    let program = fetch_program(&file, entry).await?;
+    let annotations = Annotations::read(&program);
+    let label = config.label.clone();
+    let (cache, config) = (context.cache.clone(), config.clone());
+    let output = blocking(move || cache.estimate(&program, &config).map_err(field_error)).await?;
+    let checks = annotations.check(&output);
+
+    Ok(EstimationResult::new(file, label, output).with_budget_checks(checks))
+}
+
+/// Runs compiling and estimating on actix's threads for blocking work, so a long estimation
+/// or sweep does not stall the worker that serves other requests meanwhile.
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> FieldResult<T> + Send + 'static) -> FieldResult<T> {
+    actix_web::web::block(work)
+        .await
+        .map_err(|e| FieldError::from(format!("the estimation was cancelled: {e}")))?
+}
+
+async fn fetch_program(file: &str, entry: Option<String>) -> FieldResult<Program> {
+    Ok(Program::fetch(file).await.map_err(field_error)?.with_entry(entry))
+}
+
+/// Turns a qcore error into a GraphQL error whose extensions carry its kind and every
+/// compiler diagnostic with its source locations.
+fn field_error(error: qcore::Error) -> FieldError {
+    let diagnostics = error.diagnostics().into_iter().map(diagnostic_value).collect();
+
+    let mut extensions = Object::with_capacity(2);
+    extensions.add_field("kind", Value::scalar(error.kind().to_string()));
+    extensions.add_field("diagnostics", Value::list(diagnostics));
+    FieldError::new(error, Value::object(extensions))
+}
+
+fn diagnostic_value(diagnostic: qcore::ErrorDiagnostic) -> Value {
+    let locations = diagnostic
+        .locations
+        .into_iter()
+        .map(|location| {
+            let mut value = Object::with_capacity(4);
+            value.add_field("file", optional_value(location.file));
+            value.add_field("line", Value::scalar(to_int(location.line as u64)));
+            value.add_field("column", Value::scalar(to_int(location.column as u64)));
+            value.add_field("label", optional_value(location.label));
+            Value::object(value)
+        })
+        .collect();
+
+    let mut value = Object::with_capacity(4);
+    value.add_field("message", Value::scalar(diagnostic.message));
+    value.add_field("code", optional_value(diagnostic.code));
+    value.add_field("help", optional_value(diagnostic.help));
+    value.add_field("locations", Value::list(locations));
+    Value::object(value)
+}
+
+fn optional_value(value: Option<String>) -> Value {
+    value.map_or_else(Value::null, Value::scalar)
 }
 
-pub type Schema = RootNode<'static, Query, Mutation, EmptySubscription>;
+pub type Schema = RootNode<'static, Query, Mutation, EmptySubscription<Context>>;
 
 pub fn create_schema() -> Schema {
     Schema::new(Query {}, Mutation {}, EmptySubscription::new())